@@ -0,0 +1,155 @@
+use crate::Size;
+
+/// The symbols a puzzle is written with: `symbols[v - 1]` stands for digit `v` and any of
+/// the placeholders for an empty cell, the first one being used when printing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alphabet {
+    symbols: Vec<char>,
+    placeholders: Vec<char>,
+}
+
+/// Expands ranges such as `A-F` into every character they span.
+fn expand(spec: &str) -> Option<Vec<char>> {
+    let chars: Vec<char> = spec.chars().collect();
+    let mut expanded = vec![];
+    let mut k = 0;
+    while k < chars.len() {
+        if k + 2 < chars.len() && chars[k + 1] == '-' {
+            if chars[k] > chars[k + 2] {
+                return None;
+            }
+            expanded.extend(chars[k]..=chars[k + 2]);
+            k += 3;
+        } else {
+            expanded.push(chars[k]);
+            k += 1;
+        }
+    }
+    Some(expanded)
+}
+
+impl Alphabet {
+    /// An alphabet from a symbol list where ranges may be abbreviated, e.g. `0-9A-F` for
+    /// hexadoku or `A-I` for letters. Returns `None` if there is no placeholder, a symbol
+    /// appears twice or there are more symbols than the largest board has digits.
+    pub fn new(symbols: &str, placeholders: &str) -> Option<Self> {
+        let symbols = expand(symbols)?;
+        let placeholders: Vec<char> = placeholders.chars().collect();
+        let all: Vec<char> = symbols.iter().chain(placeholders.iter()).copied().collect();
+        let distinct = (0..all.len()).all(|k| !all[k + 1..].contains(&all[k]));
+        let fits = (1..=Size::MAX_SIDE).contains(&symbols.len());
+        if placeholders.is_empty() || !fits || !distinct {
+            return None;
+        }
+        Some(Alphabet { symbols, placeholders })
+    }
+
+    /// The symbols used by default for a board: `1` to `9`, then `A` for 10 and so on.
+    pub fn standard(size: Size) -> Self {
+        Alphabet {
+            symbols: (1..=size.side() as u32)
+                .map(|v| char::from_digit(v, 36).unwrap().to_ascii_uppercase())
+                .collect(),
+            placeholders: vec!['.', '0'],
+        }
+    }
+
+    /// The number of digits the alphabet can write.
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    /// The symbol written for a digit, the first placeholder for 0, or `None` for a digit
+    /// beyond the alphabet.
+    pub fn symbol(&self, value: u8) -> Option<char> {
+        match value {
+            0 => Some(self.placeholders[0]),
+            _ => self.symbols.get((value as usize) - 1).copied(),
+        }
+    }
+
+    /// The digit a symbol stands for, 0 for a placeholder, or `None` for a foreign symbol.
+    pub fn value(&self, symbol: char) -> Option<u8> {
+        if self.placeholders.contains(&symbol) {
+            return Some(0);
+        }
+        self.symbols
+            .iter()
+            .position(|&s| s == symbol)
+            .map(|k| (k + 1) as u8)
+    }
+
+    /// Reads a puzzle written with this alphabet, or `None` if it uses foreign symbols.
+    pub fn parse(&self, puzzle: &str) -> Option<Vec<u8>> {
+        puzzle
+            .chars()
+            .map(|c| self.value(c))
+            .collect()
+    }
+
+    /// Writes a puzzle with this alphabet, or `None` if it holds a digit beyond the alphabet.
+    pub fn format(&self, puzzle: &[u8]) -> Option<String> {
+        puzzle
+            .iter()
+            .map(|&v| self.symbol(v))
+            .collect()
+    }
+}
+
+impl Default for Alphabet {
+    fn default() -> Self {
+        Alphabet::standard(Size::STANDARD)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ solve, str_to_vecu8, vecu8_to_str };
+
+    use super::*;
+
+    #[test]
+    fn test_alphabet() {
+        let hex = Alphabet::new("0-9A-F", "._").unwrap();
+        assert_eq!(hex.len(), 16);
+        assert_eq!(hex.parse("0F_.a"), None);
+        assert_eq!(hex.parse("0F_.A"), Some(vec![1, 16, 0, 0, 11]));
+        assert_eq!(hex.format(&[1, 16, 0, 11]), Some("0F.A".to_string()));
+        assert_eq!(hex.format(&[1, 17]), None);
+
+        let letters = Alphabet::new("A-I", "*").unwrap();
+        assert_eq!(letters.parse("I*A"), Some(vec![9, 0, 1]));
+        assert_eq!(letters.format(&[9, 0, 1]), Some("I*A".to_string()));
+
+        assert_eq!(Alphabet::new("A-I", "A"), None);
+        assert_eq!(Alphabet::new("1-9", ""), None);
+        assert_eq!(Alphabet::new("9-1", "."), None);
+        assert_eq!(Alphabet::new("A-Z", "."), None);
+        assert_eq!(Alphabet::new("\u{100}-\u{2ff}", "."), None);
+        assert_eq!(Alphabet::new("A-Y", ".").map(|letters| letters.len()), Some(25));
+        assert_eq!(Alphabet::default().parse("0.19"), Some(vec![0, 0, 1, 9]));
+        assert_eq!(Alphabet::standard(Size::new(4)).symbol(16), Some('G'));
+        assert_eq!(Alphabet::standard(Size::new(4)).symbol(17), None);
+        assert_eq!(Alphabet::default().symbol(0), Some('.'));
+    }
+
+    #[test]
+    fn test_wordoku() {
+        let letters = Alphabet::new("ADEGLNORS", "_").unwrap();
+        let puzzle =
+            ".5..83.17...1..4..3.4..56.8....3...9.9.8245....6....7...9....5...729..861.36.72.4";
+        let wordoku = letters.format(&str_to_vecu8(puzzle)).unwrap();
+        assert!(wordoku.starts_with("_L__RE_AO"));
+
+        let solution = solve(&vecu8_to_str(&letters.parse(&wordoku).unwrap()), Size::STANDARD);
+        let solution = letters.format(&str_to_vecu8(&solution.unwrap())).unwrap();
+        assert_eq!(
+            letters.parse(&solution).map(|p| vecu8_to_str(&p)),
+            solve(puzzle, Size::STANDARD)
+        );
+    }
+}
@@ -0,0 +1,184 @@
+use std::ops::Range;
+
+use crate::{
+    are_touching,
+    constraint::digits,
+    is_connected,
+    utils::{ clue_lines, parse_cells },
+    Size,
+};
+
+/// An arrow: the digits along its shaft add up to the number in its circle, which may be a
+/// pill of several cells read as a decimal number from its first cell. Shaft digits may repeat
+/// unless a house forbids it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Arrow {
+    /// The cells of the circle first, then those of the shaft from the circle outwards.
+    pub cells: Vec<usize>,
+    /// The number of cells in the circle.
+    pub circle: usize,
+}
+
+fn lowest(mask: u32) -> u32 {
+    mask.trailing_zeros()
+}
+
+fn highest(mask: u32) -> u32 {
+    31 - mask.leading_zeros()
+}
+
+impl Arrow {
+    /// Narrows the candidates by intervals: the sums the shaft can reach and the numbers the
+    /// circle can show must meet, and each cell keeps the digits which let them meet given the
+    /// range of the other cells, until nothing changes.
+    pub fn prune(&self, masks: &mut [u32]) {
+        let weights: Vec<u32> = (0..masks.len())
+            .map(|k| if k < self.circle { (10u32).pow((self.circle - 1 - k) as u32) } else { 1 })
+            .collect();
+        loop {
+            if masks.contains(&0) {
+                masks.fill(0);
+                return;
+            }
+            let range = |ks: Range<usize>| {
+                ks.fold((0, 0), |(low, high), k| {
+                    (low + weights[k] * lowest(masks[k]), high + weights[k] * highest(masks[k]))
+                })
+            };
+            let circle = range(0..self.circle);
+            let shaft = range(self.circle..masks.len());
+            let (low, high) = (circle.0.max(shaft.0), circle.1.min(shaft.1));
+
+            let mut changed = false;
+            for k in 0..masks.len() {
+                let (others_low, others_high) = {
+                    let (part_low, part_high) = if k < self.circle { circle } else { shaft };
+                    let w = weights[k];
+                    (part_low - w * lowest(masks[k]), part_high - w * highest(masks[k]))
+                };
+                let kept = digits(masks[k])
+                    .filter(|&v| {
+                        let value = weights[k] * u32::from(v);
+                        value + others_low <= high && value + others_high >= low
+                    })
+                    .fold(0, |kept, v| kept | (1 << v));
+                changed |= kept != masks[k];
+                masks[k] = kept;
+            }
+            if !changed {
+                return;
+            }
+        }
+    }
+}
+
+/// Reads arrows, one per line of `clue_lines` as the cells of the circle, a colon and the cells
+/// of the shaft from the circle outwards, e.g. `r1c1: r2c2 r3c3` or `r1c1 r1c2: r2c3 r3c3 r4c3`
+/// for a pill. Returns `None` unless the circle is connected, has no more cells than the
+/// largest sum of the shaft has digits, no cell appears twice and the shaft leaves the circle
+/// going from cell to cell by a side or a corner.
+pub fn parse_arrows(text: &str, size: Size) -> Option<Vec<Arrow>> {
+    let mut arrows = vec![];
+    for line in clue_lines(text) {
+        let (circle, shaft) = line.split_once(':')?;
+        let (circle, shaft) = (parse_cells(circle, size)?, parse_cells(shaft, size)?);
+        if circle.is_empty() || shaft.is_empty() || !is_connected(&circle, size) {
+            return None;
+        }
+        let largest_sum = shaft.len() * size.side();
+        if circle.len() > largest_sum.to_string().len() {
+            return None;
+        }
+        let cells: Vec<usize> = circle.iter().chain(shaft.iter()).copied().collect();
+        let leaves = circle.iter().any(|&i| are_touching(i, shaft[0], size));
+        if
+            (0..cells.len()).any(|k| cells[k + 1..].contains(&cells[k])) ||
+            !leaves ||
+            !shaft.windows(2).all(|pair| are_touching(pair[0], pair[1], size))
+        {
+            return None;
+        }
+        arrows.push(Arrow { cells, circle: circle.len() });
+    }
+    Some(arrows)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ has_unique_solution_in, solve_in, Layout };
+
+    use super::*;
+
+    const ARROWS: &str =
+        "r4c7 r4c8: r5c6 r4c5 r4c4
+r5c2: r6c3 r7c4
+r5c1: r4c2 r3c3 r3c2
+r2c1: r2c2 r1c3
+r1c5: r1c6 r2c7 r1c8
+r8c7: r9c7 r8c6
+r8c1: r9c1 r8c2";
+    const PUZZLE: &str =
+        "...........8....3.........8..............4......51...2.......5.......3.....6.7...";
+    const SOLUTION: &str =
+        "652483917978162435314975628825736149791824563436519872269348751547291386183657294";
+
+    #[test]
+    fn test_parse_arrows() {
+        let arrows = parse_arrows(ARROWS, Size::STANDARD).unwrap();
+        assert_eq!(arrows.len(), 7);
+        assert_eq!(arrows[0], Arrow { cells: vec![33, 34, 41, 31, 30], circle: 2 });
+        assert_eq!(arrows[1], Arrow { cells: vec![37, 47, 57], circle: 1 });
+
+        // no circle, a pill in pieces, a shaft away from the circle, a gap and a cell twice
+        assert_eq!(parse_arrows("r1c1 r1c2", Size::STANDARD), None);
+        assert_eq!(parse_arrows("r1c1 r1c3: r2c2", Size::STANDARD), None);
+        assert_eq!(parse_arrows("r1c1: r3c3", Size::STANDARD), None);
+        assert_eq!(parse_arrows("r1c1: r2c2 r4c4", Size::STANDARD), None);
+        assert_eq!(parse_arrows("r1c1: r2c2 r1c1", Size::STANDARD), None);
+
+        // a pill wider than any sum of its shaft, down to one which would overflow the prune
+        assert_eq!(parse_arrows("r1c1 r1c2: r2c2", Size::STANDARD), None);
+        assert!(parse_arrows("r1c1 r1c2: r2c2 r3c2", Size::STANDARD).is_some());
+        let pill: Vec<String> = (1..=11).map(|c| format!("r1c{}", c)).collect();
+        let shaft: Vec<String> = (1..=11).map(|c| format!("r2c{}", c)).collect();
+        let line = format!("{}: {}", pill.join(" "), shaft.join(" "));
+        assert_eq!(parse_arrows(&line, Size::new(4)), None);
+    }
+
+    #[test]
+    fn test_prune_arrow() {
+        let all = 0b11_1111_1110;
+
+        let arrow = Arrow { cells: vec![0, 1, 2], circle: 1 };
+        let mut masks = vec![all; 3];
+        arrow.prune(&mut masks);
+        assert_eq!(masks, vec![all & !0b10, 0b1_1111_1110, 0b1_1111_1110]);
+
+        // a pill over two cells shows 11 to 18
+        let arrow = Arrow { cells: vec![0, 1, 2, 3], circle: 2 };
+        let mut masks = vec![all; 4];
+        arrow.prune(&mut masks);
+        assert_eq!(masks, vec![1 << 1, 0b1_1111_1110, all & !0b10, all & !0b10]);
+
+        let mut masks = vec![1 << 1, 1 << 2, 1 << 9, all];
+        arrow.prune(&mut masks);
+        assert_eq!(masks, vec![1 << 1, 1 << 2, 1 << 9, 1 << 3]);
+
+        let arrow = Arrow { cells: vec![0, 1, 2], circle: 1 };
+        let mut masks = vec![1 << 1, all, all];
+        arrow.prune(&mut masks);
+        assert!(masks.contains(&0));
+    }
+
+    #[test]
+    fn test_solve_arrows() {
+        let arrows = parse_arrows(ARROWS, Size::STANDARD).unwrap();
+        let layout = Layout::arrows(Size::STANDARD, &arrows);
+        assert_eq!(solve_in(PUZZLE, &layout), Some(SOLUTION.to_string()));
+        assert!(has_unique_solution_in(PUZZLE, &layout));
+        assert!(!has_unique_solution_in(PUZZLE, &Layout::standard()));
+
+        // a 9 in the pill r4c7 r4c8 is beyond the reach of its three cell shaft
+        assert_eq!(layout.parse(&format!("{}9{}", ".".repeat(33), ".".repeat(47))), None);
+    }
+}
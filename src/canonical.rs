@@ -0,0 +1,224 @@
+use crate::{ str_to_vecu8, Transform };
+
+const PERMUTATIONS: [[usize; 3]; 6] = [
+    [0, 1, 2],
+    [0, 2, 1],
+    [1, 0, 2],
+    [1, 2, 0],
+    [2, 0, 1],
+    [2, 1, 0],
+];
+
+/// How the best grid so far was reached.
+#[derive(Debug, Clone, Default)]
+struct Parts {
+    transposed: bool,
+    stacks: [usize; 3],
+    cols: [[usize; 3]; 3],
+    bands: [usize; 3],
+    rows: [[usize; 3]; 3],
+    labels: [u8; 10],
+}
+
+struct Search {
+    grid: Vec<u8>,
+    col_order: [usize; 9],
+    current: Parts,
+    cells: [u8; 81],
+    best: Option<([u8; 81], Parts)>,
+}
+
+impl Search {
+    /// Fills output row `k` with each input row the band structure allows, keeping the
+    /// branches which can still beat the best grid.
+    fn rows(&mut self, k: usize, labels: [u8; 10], next_label: u8) {
+        if k == 9 {
+            if self.best.as_ref().is_none_or(|(best, _)| self.cells < *best) {
+                let mut parts = self.current.clone();
+                parts.labels = labels;
+                self.best = Some((self.cells, parts));
+            }
+            return;
+        }
+
+        let band = k / 3;
+        let bands: Vec<usize> = if k.is_multiple_of(3) {
+            (0..3).filter(|b| !self.current.bands[..band].contains(b)).collect()
+        } else {
+            vec![self.current.bands[band]]
+        };
+        for b in bands {
+            self.current.bands[band] = b;
+            for r in 0..3 {
+                if self.current.rows[band][..k % 3].contains(&r) {
+                    continue;
+                }
+                self.current.rows[band][k % 3] = r;
+
+                let mut labels = labels;
+                let mut next_label = next_label;
+                for j in 0..9 {
+                    let v = self.grid[(b * 3 + r) * 9 + self.col_order[j]] as usize;
+                    if v != 0 && labels[v] == 0 {
+                        labels[v] = next_label;
+                        next_label += 1;
+                    }
+                    self.cells[k * 9 + j] = labels[v];
+                }
+
+                let end = (k + 1) * 9;
+                if self.best.as_ref().is_some_and(|(best, _)| self.cells[..end] > best[..end]) {
+                    continue;
+                }
+                self.rows(k + 1, labels, next_label);
+            }
+        }
+    }
+}
+
+fn transform_of(parts: &Parts) -> Transform {
+    let mut transform = if parts.transposed {
+        Transform::transpose()
+    } else {
+        Transform::identity()
+    };
+    transform = transform.then(&Transform::permute_stacks(parts.stacks));
+    for (s, &perm) in parts.cols.iter().enumerate() {
+        transform = transform.then(&Transform::permute_cols(s, perm));
+    }
+    transform = transform.then(&Transform::permute_bands(parts.bands));
+    for (b, &perm) in parts.rows.iter().enumerate() {
+        transform = transform.then(&Transform::permute_rows(b, perm));
+    }
+
+    // digits missing from the puzzle take the labels left over, in order
+    let mut labels = parts.labels;
+    let mut next_label = labels.iter().max().copied().unwrap_or(0) + 1;
+    let mut digits = [0; 9];
+    for v in 1..=9 {
+        if labels[v] == 0 {
+            labels[v] = next_label;
+            next_label += 1;
+        }
+        digits[v - 1] = labels[v];
+    }
+
+    transform.then(&Transform::relabel(digits))
+}
+
+/// The minimal lexicographic (minlex) form of a puzzle or grid over all transpositions,
+/// band, stack, row and column permutations and relabelings, with the transform leading to
+/// it. Equivalent puzzles share the same form. Returns `None` unless the puzzle has 81 cells.
+pub fn canonical_form(puzzle: &str) -> Option<(String, Transform)> {
+    let grid = str_to_vecu8(puzzle);
+    if grid.len() != 81 {
+        return None;
+    }
+    let mut search = Search {
+        grid: vec![],
+        col_order: [0; 9],
+        current: Parts::default(),
+        cells: [0; 81],
+        best: None,
+    };
+
+    for transposed in [false, true] {
+        search.grid = if transposed {
+            Transform::transpose().apply_vec(&grid)?
+        } else {
+            grid.clone()
+        };
+        search.current.transposed = transposed;
+        for stacks in PERMUTATIONS {
+            let col_perms = PERMUTATIONS.iter().flat_map(|&a| {
+                PERMUTATIONS.iter().flat_map(move |&b| PERMUTATIONS.iter().map(move |&c| [a, b, c]))
+            });
+            for cols in col_perms {
+                for j in 0..9 {
+                    search.col_order[j] = stacks[j / 3] * 3 + cols[j / 3][j % 3];
+                }
+                search.current.stacks = stacks;
+                search.current.cols = cols;
+                search.rows(0, [0; 10], 1);
+            }
+        }
+    }
+
+    let (_, parts) = search.best?;
+    let transform = transform_of(&parts);
+    Some((transform.apply(puzzle)?, transform))
+}
+
+/// The minlex form of a puzzle, see [`canonical_form`].
+pub fn canonicalize(puzzle: &str) -> Option<String> {
+    canonical_form(puzzle).map(|(canonical, _)| canonical)
+}
+
+/// The transform mapping puzzle `a` onto puzzle `b`, if they are equivalent.
+pub fn are_equivalent(a: &str, b: &str) -> Option<Transform> {
+    let (canonical_a, transform_a) = canonical_form(a)?;
+    let (canonical_b, transform_b) = canonical_form(b)?;
+    if canonical_a != canonical_b {
+        return None;
+    }
+    Some(transform_a.then(&transform_b.inverse()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vecu8_to_str;
+
+    const PUZZLE: &str =
+        ".5..83.17...1..4..3.4..56.8....3...9.9.8245....6....7...9....5...729..861.36.72.4";
+    const SOLUTION: &str =
+        "652483917978162435314975628825736149791824563436519872269348751547291386183657294";
+
+    #[test]
+    fn test_canonicalize() {
+        let canonical = canonicalize(SOLUTION).unwrap();
+        assert!(canonical.starts_with("123456789"));
+        assert!(canonical <= vecu8_to_str(&str_to_vecu8(SOLUTION)));
+
+        let disguised = Transform::rotate()
+            .then(&Transform::permute_rows(2, [1, 0, 2]))
+            .then(&Transform::relabel([4, 5, 6, 7, 8, 9, 1, 2, 3]))
+            .apply(PUZZLE)
+            .unwrap();
+        let canonical_puzzle = canonicalize(PUZZLE).unwrap();
+        assert_eq!(canonicalize(&disguised), Some(canonical_puzzle.clone()));
+        assert_eq!(canonicalize(&canonical_puzzle), Some(canonical_puzzle));
+
+        let shuffled = Transform::permute_stacks([1, 2, 0])
+            .then(&Transform::mirror_horizontal())
+            .then(&Transform::relabel([2, 3, 1, 5, 6, 4, 8, 9, 7]))
+            .apply(SOLUTION)
+            .unwrap();
+        assert_eq!(canonicalize(&shuffled), Some(canonical));
+    }
+
+    #[test]
+    fn test_wrong_length() {
+        assert_eq!(canonicalize(""), None);
+        assert_eq!(canonicalize(&".".repeat(80)), None);
+        assert!(canonical_form(&format!("{}.", PUZZLE)).is_none());
+        assert_eq!(are_equivalent("1", "2"), None);
+        assert_eq!(are_equivalent(PUZZLE, &PUZZLE[1..]), None);
+    }
+
+    #[test]
+    fn test_are_equivalent() {
+        let disguise = Transform::transpose()
+            .then(&Transform::permute_bands([2, 0, 1]))
+            .then(&Transform::permute_cols(1, [2, 1, 0]))
+            .then(&Transform::relabel([9, 1, 8, 2, 7, 3, 6, 4, 5]));
+        let disguised = disguise.apply(PUZZLE).unwrap();
+
+        let transform = are_equivalent(PUZZLE, &disguised).unwrap();
+        assert_eq!(transform.apply(PUZZLE).unwrap(), disguised);
+
+        let other =
+            "2.6.3......1.65.7..471.8.5.5......29..8.194.6...42...1....428..6.93....5.7.....13";
+        assert_eq!(are_equivalent(PUZZLE, other), None);
+    }
+}
@@ -0,0 +1,114 @@
+use crate::Size;
+
+/// A chess piece whose move forbids equal digits, as in anti-knight and anti-king Sudoku:
+/// two cells a move apart must hold different digits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Piece {
+    Knight,
+    /// Only the diagonal steps count, the others being in the same row or column anyway.
+    King,
+}
+
+impl Piece {
+    fn steps(&self) -> &'static [(isize, isize)] {
+        match self {
+            Piece::Knight =>
+                &[(-2, -1), (-2, 1), (-1, -2), (-1, 2), (1, -2), (1, 2), (2, -1), (2, 1)],
+            Piece::King => &[(-1, -1), (-1, 1), (1, -1), (1, 1)],
+        }
+    }
+
+    /// The cells one move away from cell `i`, in increasing order.
+    pub fn moves(&self, i: usize, size: Size) -> Vec<usize> {
+        let side = size.side() as isize;
+        let (r, c) = (size.row_of(i) as isize, size.col_of(i) as isize);
+        self.steps()
+            .iter()
+            .map(|&(dr, dc)| (r + dr, c + dc))
+            .filter(|&(r, c)| (0..side).contains(&r) && (0..side).contains(&c))
+            .map(|(r, c)| (r * side + c) as usize)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{ cell::RefCell, collections::HashMap, rc::Rc };
+
+    use crate::{ has_unique_solution_in, solve_in, str_to_vecu8, GridTask, Layout };
+
+    use super::*;
+
+    const ANTI_KNIGHT: &str =
+        "...............7...24....3........8.....2.6.......5...6................3...35...1";
+    const ANTI_KNIGHT_SOLUTION: &str =
+        "376289154589134726124567839215496387497823615863715492632971548951648273748352961";
+    const ANTI_KING: &str =
+        ".....31......5.....96....57.....6.4.............5.42....8.....57.......1.3987....";
+    const ANTI_KING_SOLUTION: &str =
+        "857693124341257689296418357512736948684921573973584216428169735765342891139875462";
+
+    fn layout_with(pieces: &[Piece]) -> Layout {
+        let mut layout = Layout::standard();
+        for &piece in pieces {
+            layout.forbid_moves(piece);
+        }
+        layout
+    }
+
+    #[test]
+    fn test_moves() {
+        assert_eq!(Piece::Knight.moves(0, Size::STANDARD), vec![11, 19]);
+        assert_eq!(Piece::Knight.moves(40, Size::STANDARD).len(), 8);
+        assert_eq!(Piece::King.moves(40, Size::STANDARD), vec![30, 32, 48, 50]);
+        assert_eq!(Piece::King.moves(3, Size::new(2)), vec![6]);
+    }
+
+    #[test]
+    fn test_forbid_moves() {
+        let mut layout = layout_with(&[Piece::Knight, Piece::King]);
+        assert_eq!(layout.pieces(), &[Piece::Knight, Piece::King]);
+        assert_eq!(layout.peers(38).len(), 28);
+        assert_eq!(layout.move_peers(0), &[10, 11, 19]);
+
+        layout.allow_moves(Piece::Knight);
+        assert_eq!(layout.peers(38).len(), 22);
+        layout.allow_moves(Piece::King);
+        assert_eq!(layout, Layout::standard());
+
+        // r1c4 and r2c2 are a knight's move apart
+        let puzzle = format!("...1{}1{}", ".".repeat(6), ".".repeat(70));
+        assert_eq!(layout_with(&[Piece::Knight]).parse(&puzzle), None);
+        assert!(layout_with(&[Piece::King]).parse(&puzzle).is_some());
+    }
+
+    #[test]
+    fn test_move_candidates() {
+        let puzzle = Rc::new(RefCell::new(str_to_vecu8(&format!("1{}", ".".repeat(80)))));
+        let possible_values_map = Rc::new(RefCell::new(HashMap::new()));
+        GridTask::calculate_all_possible_values(
+            Rc::clone(&puzzle),
+            Rc::clone(&possible_values_map),
+            Rc::new(layout_with(&[Piece::Knight]))
+        );
+        let possible_values_map = possible_values_map.borrow();
+        assert!(!possible_values_map[&11].contains(&1));
+        assert!(possible_values_map[&12].contains(&1));
+    }
+
+    #[test]
+    fn test_solve_anti_knight() {
+        let layout = layout_with(&[Piece::Knight]);
+        assert_eq!(solve_in(ANTI_KNIGHT, &layout), Some(ANTI_KNIGHT_SOLUTION.to_string()));
+        assert!(has_unique_solution_in(ANTI_KNIGHT, &layout));
+        assert!(!has_unique_solution_in(ANTI_KNIGHT, &Layout::standard()));
+    }
+
+    #[test]
+    fn test_solve_anti_king() {
+        let layout = layout_with(&[Piece::King]);
+        assert_eq!(solve_in(ANTI_KING, &layout), Some(ANTI_KING_SOLUTION.to_string()));
+        assert!(has_unique_solution_in(ANTI_KING, &layout));
+        assert!(!has_unique_solution_in(ANTI_KING, &Layout::standard()));
+    }
+}
@@ -0,0 +1,68 @@
+use crate::{
+    arrow::Arrow,
+    killer::Cage,
+    kropki::Dot,
+    sandwich::Sandwich,
+    thermo::Thermometer,
+    xv::Xv,
+};
+
+/// A rule beyond the all-different houses, tying the digits of a few cells together.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Constraint {
+    Cage(Cage),
+    Dot(Dot),
+    Xv(Xv),
+    Thermometer(Thermometer),
+    Arrow(Arrow),
+    Sandwich(Sandwich),
+}
+
+impl Constraint {
+    pub fn cells(&self) -> &[usize] {
+        match self {
+            Constraint::Cage(cage) => &cage.cells,
+            Constraint::Dot(dot) => &dot.cells,
+            Constraint::Xv(marker) => &marker.cells,
+            Constraint::Thermometer(thermometer) => &thermometer.cells,
+            Constraint::Arrow(arrow) => &arrow.cells,
+            Constraint::Sandwich(sandwich) => &sandwich.cells,
+        }
+    }
+
+    /// Narrows `masks`, the candidates of `cells()` in the same order with bit `v` standing
+    /// for digit `v`, to the digits which still fit. A filled cell has its digit as the only
+    /// candidate. A mask left empty means the constraint cannot be met.
+    pub fn prune(&self, masks: &mut [u32], side: usize) {
+        match self {
+            Constraint::Cage(cage) => cage.prune(masks, side),
+            Constraint::Dot(dot) => dot.prune(masks),
+            Constraint::Xv(marker) => marker.prune(masks),
+            Constraint::Thermometer(thermometer) => thermometer.prune(masks),
+            Constraint::Arrow(arrow) => arrow.prune(masks),
+            Constraint::Sandwich(sandwich) => sandwich.prune(masks, side),
+        }
+    }
+}
+
+/// The digits of a candidate mask, in increasing order.
+pub fn digits(mask: u32) -> impl Iterator<Item = u8> {
+    (1..32u8).filter(move |&v| mask & (1 << v) != 0)
+}
+
+/// Keeps the candidates of each of two cells which go with some candidate of the other, as
+/// told by `fits`.
+pub fn prune_pair(masks: &mut [u32], fits: impl Fn(u8, u8) -> bool) {
+    let supported = |mask: u32, other: u32| {
+        digits(mask)
+            .filter(|&a| digits(other).any(|b| fits(a, b)))
+            .fold(0, |support, a| support | (1 << a))
+    };
+    masks[0] = supported(masks[0], masks[1]);
+    masks[1] = supported(masks[1], masks[0]);
+}
+
+/// The mask holding every digit of a board with `side` digits.
+pub fn all_digits(side: usize) -> u32 {
+    ((1u32 << (side + 1)) - 1) & !1
+}
@@ -0,0 +1,72 @@
+use crate::{ render_marked, Size };
+
+/// The mark in front of a cell of a Sudoku-X grid: `\` on the main diagonal, `/` on the
+/// anti-diagonal and `X` where they cross.
+fn diagonal_mark(i: usize, size: Size) -> char {
+    let (r, c) = (size.row_of(i), size.col_of(i));
+    match (r == c, r + c + 1 == size.side()) {
+        (true, true) => 'X',
+        (true, false) => '\\',
+        (false, true) => '/',
+        (false, false) => ' ',
+    }
+}
+
+/// The boxed grid of `show` with the cells of both diagonals marked.
+pub fn render_diagonal(sudoku: &str, size: Size) -> String {
+    render_marked(sudoku, size, |i| diagonal_mark(i, size))
+}
+
+pub fn show_diagonal(sudoku: &str, size: Size) {
+    print!("{}", render_diagonal(sudoku, size));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{ cell::RefCell, collections::HashMap, rc::Rc };
+
+    use crate::{ has_unique_solution_in, solve_in, vecu8_to_str, GridTask, Layout, Propagation };
+
+    use super::*;
+
+    const PUZZLE: &str =
+        ".23...7...5..8..................1.....75...9.........1..8.1.6.5..1..5...5..972..8";
+    const SOLUTION: &str =
+        "123456789456789123789123456935241867617538294842697531298314675371865942564972318";
+
+    #[test]
+    fn test_diagonal_layout() {
+        let layout = Layout::diagonal(Size::STANDARD);
+        assert_eq!(layout.peers(0).len(), 26);
+        assert_eq!(layout.peers(40).len(), 32);
+        assert_eq!(layout.parse(&format!("1{}1", ".".repeat(79))), None);
+        assert!(Layout::standard().parse(&format!("1{}1", ".".repeat(79))).is_some());
+    }
+
+    #[test]
+    fn test_solve_diagonal() {
+        let layout = Layout::diagonal(Size::STANDARD);
+        assert_eq!(solve_in(PUZZLE, &layout), Some(SOLUTION.to_string()));
+        assert!(has_unique_solution_in(PUZZLE, &layout));
+        assert!(!has_unique_solution_in(PUZZLE, &Layout::standard()));
+
+        let puzzle = Rc::new(RefCell::new(layout.parse(PUZZLE).unwrap()));
+        let propagation = GridTask::propagate(
+            Rc::clone(&puzzle),
+            Rc::new(RefCell::new(HashMap::new())),
+            Rc::new(layout)
+        );
+        assert!(matches!(propagation, Propagation::Solved));
+        assert_eq!(vecu8_to_str(&puzzle.borrow()), SOLUTION);
+    }
+
+    #[test]
+    fn test_render_diagonal() {
+        let rendered = render_diagonal(SOLUTION, Size::STANDARD);
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines[1], "|\\1 2 3| 4 5 6| 7 8/9|");
+        assert_eq!(lines[6], "| 6 1 7| 5X3 8| 2 9 4|");
+        assert_eq!(lines[11], "|/5 6 4| 9 7 2| 3 1\\8|");
+        show_diagonal(PUZZLE, Size::STANDARD);
+    }
+}
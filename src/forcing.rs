@@ -0,0 +1,338 @@
+use std::{ cell::RefCell, collections::HashMap, fmt, rc::Rc };
+
+use crate::{ cell_name_sized, grid_task::Propagation, GridTask, House, Layout, Size };
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForcingKind {
+    /// Every candidate of one cell is tried.
+    Cell(usize),
+    /// Every position of one digit inside a house is tried.
+    Unit(House, u8),
+}
+
+impl ForcingKind {
+    /// The kind as text, naming the cell as on a board of the given size.
+    pub fn describe(&self, size: Size) -> String {
+        match *self {
+            ForcingKind::Cell(i) => format!("cell forcing chain on {}", cell_name_sized(i, size)),
+            ForcingKind::Unit(house, v) => format!("unit forcing chain on {} in {}", v, house),
+        }
+    }
+}
+
+impl fmt::Display for ForcingKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.describe(Size::STANDARD))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Branch {
+    pub index: usize,
+    pub value: u8,
+    pub contradiction: bool,
+}
+
+/// Conclusions shared by every branch of a cell or a unit, found by placing each
+/// alternative in turn and running the singles on the result.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForcingChain {
+    kind: ForcingKind,
+    branches: Vec<Branch>,
+    placements: Vec<(usize, u8)>,
+}
+
+impl ForcingChain {
+    pub fn kind(&self) -> ForcingKind {
+        self.kind
+    }
+
+    pub fn branches(&self) -> &[Branch] {
+        &self.branches
+    }
+
+    /// The cells filled identically by every branch which does not fail.
+    pub fn placements(&self) -> &[(usize, u8)] {
+        &self.placements
+    }
+
+    /// Every branch fails, so the puzzle has no solution.
+    pub fn is_contradiction(&self) -> bool {
+        self.branches.iter().all(|b| b.contradiction)
+    }
+}
+
+impl ForcingChain {
+    /// The chain as text, naming the cells as on a board of the given size.
+    pub fn describe(&self, size: Size) -> String {
+        let cell_name = |i: usize| cell_name_sized(i, size);
+        let branches: Vec<String> = self.branches
+            .iter()
+            .map(|b| {
+                let assumption = format!("{}={}", cell_name(b.index), b.value);
+                if b.contradiction { format!("{} fails", assumption) } else { assumption }
+            })
+            .collect();
+        let conclusion = if self.is_contradiction() {
+            String::from("no solution")
+        } else {
+            self.placements
+                .iter()
+                .map(|&(i, v)| format!("{}={}", cell_name(i), v))
+                .collect::<Vec<String>>()
+                .join(", ")
+        };
+
+        format!("{}: {} => {}", self.kind.describe(size), branches.join(", "), conclusion)
+    }
+}
+
+impl fmt::Display for ForcingChain {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.describe(Size::STANDARD))
+    }
+}
+
+fn propagate_assumption(
+    puzzle: &[u8],
+    possible_values_map: &HashMap<usize, Rc<Vec<u8>>>,
+    layout: &Rc<Layout>,
+    index: usize,
+    value: u8
+) -> Option<Vec<u8>> {
+    let mut assumed = puzzle.to_vec();
+    assumed[index] = value;
+    let assumed = Rc::new(RefCell::new(assumed));
+
+    match
+        GridTask::propagate(
+            Rc::clone(&assumed),
+            Rc::new(RefCell::new(possible_values_map.clone())),
+            Rc::clone(layout)
+        )
+    {
+        Propagation::Contradiction => None,
+        _ => Some(assumed.borrow().clone()),
+    }
+}
+
+fn try_branches(
+    puzzle: &[u8],
+    possible_values_map: &HashMap<usize, Rc<Vec<u8>>>,
+    layout: &Rc<Layout>,
+    kind: ForcingKind,
+    assumptions: Vec<(usize, u8)>,
+    outcomes: &mut HashMap<(usize, u8), Option<Vec<u8>>>
+) -> Option<ForcingChain> {
+    let mut branches = vec![];
+    // the placements shared by the surviving branches so far, `None` until one survives
+    let mut common: Option<Vec<(usize, u8)>> = None;
+    for (index, value) in assumptions {
+        let outcome = outcomes
+            .entry((index, value))
+            .or_insert_with(|| {
+                propagate_assumption(puzzle, possible_values_map, layout, index, value)
+            });
+        branches.push(Branch { index, value, contradiction: outcome.is_none() });
+
+        if let Some(result) = outcome {
+            let placements: Vec<(usize, u8)> = match common {
+                Some(placements) =>
+                    placements
+                        .into_iter()
+                        .filter(|&(i, v)| result[i] == v)
+                        .collect(),
+                None =>
+                    (0..puzzle.len())
+                        .filter(|&i| puzzle[i] == 0)
+                        .map(|i| (i, result[i]))
+                        .filter(|&(_, v)| v != 0)
+                        .collect(),
+            };
+            if placements.is_empty() {
+                return None;
+            }
+            common = Some(placements);
+        }
+    }
+
+    Some(ForcingChain { kind, branches, placements: common.unwrap_or_default() })
+}
+
+/// Looks for a cell forcing chain first and a unit forcing chain second, given a puzzle
+/// whose singles are exhausted and the possible values of its empty cells.
+pub fn find_forcing_chain(
+    puzzle: &[u8],
+    possible_values_map: &HashMap<usize, Rc<Vec<u8>>>
+) -> Option<ForcingChain> {
+    find_forcing_chain_in(puzzle, possible_values_map, &Rc::new(Layout::standard()))
+}
+
+/// Same as `find_forcing_chain` for a puzzle with the given layout, whose complete houses are
+/// the units tried. The search gives up once it has run the singles on twice as many branches
+/// as the board has cells, which leaves wide open grids to a guess.
+pub fn find_forcing_chain_in(
+    puzzle: &[u8],
+    possible_values_map: &HashMap<usize, Rc<Vec<u8>>>,
+    layout: &Rc<Layout>
+) -> Option<ForcingChain> {
+    let mut budget = usize::MAX;
+    find_forcing_chain_within(puzzle, possible_values_map, layout, &mut budget)
+}
+
+/// Same as `find_forcing_chain_in`, giving up as well once `budget` runs out. The branches
+/// of a search which finds no chain are drawn from the budget.
+pub(crate) fn find_forcing_chain_within(
+    puzzle: &[u8],
+    possible_values_map: &HashMap<usize, Rc<Vec<u8>>>,
+    layout: &Rc<Layout>,
+    budget: &mut usize
+) -> Option<ForcingChain> {
+    let limit = (*budget).min(2 * layout.size().cells());
+    let mut outcomes: HashMap<(usize, u8), Option<Vec<u8>>> = HashMap::new();
+    let chain = search(puzzle, possible_values_map, layout, limit, &mut outcomes);
+    if chain.is_none() {
+        *budget = budget.saturating_sub(outcomes.len());
+    }
+    chain
+}
+
+fn search(
+    puzzle: &[u8],
+    possible_values_map: &HashMap<usize, Rc<Vec<u8>>>,
+    layout: &Rc<Layout>,
+    limit: usize,
+    outcomes: &mut HashMap<(usize, u8), Option<Vec<u8>>>
+) -> Option<ForcingChain> {
+    let mut cells: Vec<usize> = (0..puzzle.len())
+        .filter(|&i| puzzle[i] == 0 && possible_values_map.contains_key(&i))
+        .collect();
+    cells.sort_by_key(|i| possible_values_map[i].len());
+
+    for index in cells {
+        if outcomes.len() >= limit {
+            return None;
+        }
+        let assumptions = possible_values_map[&index]
+            .iter()
+            .map(|&v| (index, v))
+            .collect();
+        let chain = try_branches(
+            puzzle,
+            possible_values_map,
+            layout,
+            ForcingKind::Cell(index),
+            assumptions,
+            outcomes
+        );
+        if chain.is_some() {
+            return chain;
+        }
+    }
+
+    let units = (0..layout.houses().len()).filter(|&h| layout.is_complete(h));
+    for (house, indexes) in units.map(|h| &layout.houses()[h]) {
+        for value in 1..=layout.size().side() as u8 {
+            if indexes.iter().any(|&i| puzzle[i] == value) {
+                continue;
+            }
+            if outcomes.len() >= limit {
+                return None;
+            }
+            let assumptions = indexes
+                .iter()
+                .filter(|&&i| puzzle[i] == 0)
+                .filter(|i| possible_values_map.get(i).is_some_and(|vs| vs.contains(&value)))
+                .map(|&i| (i, value))
+                .collect();
+            let chain = try_branches(
+                puzzle,
+                possible_values_map,
+                layout,
+                ForcingKind::Unit(*house, value),
+                assumptions,
+                outcomes
+            );
+            if chain.is_some() {
+                return chain;
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::str_to_vecu8;
+
+    use super::*;
+
+    fn stalled(puzzle: &str) -> (Vec<u8>, HashMap<usize, Rc<Vec<u8>>>) {
+        let puzzle = Rc::new(RefCell::new(str_to_vecu8(puzzle)));
+        let possible_values_map = Rc::new(RefCell::new(HashMap::new()));
+        let propagation = GridTask::propagate(
+            Rc::clone(&puzzle),
+            Rc::clone(&possible_values_map),
+            Rc::new(Layout::standard())
+        );
+        match propagation {
+            Propagation::Stalled(_) => (),
+            _ => panic!("singles should stall on this puzzle"),
+        }
+        let puzzle = puzzle.borrow().clone();
+        let possible_values_map = possible_values_map.borrow().clone();
+        (puzzle, possible_values_map)
+    }
+
+    #[test]
+    fn test_find_forcing_chain() {
+        let input =
+            "........8..3...4...9..2..6.....79.......612...6.5.2.7...8...5...1.....2.4.5.....3";
+        let solution =
+            "621943758783615492594728361142879635357461289869532174238197546916354827475286913";
+        let (puzzle, possible_values_map) = stalled(input);
+
+        let chain = find_forcing_chain(&puzzle, &possible_values_map).unwrap();
+        assert!(!chain.is_contradiction());
+        assert!(!chain.placements().is_empty());
+        for &(i, v) in chain.placements() {
+            assert_eq!(solution.as_bytes()[i] - b'0', v);
+        }
+        assert!(chain.to_string().starts_with("cell forcing chain on r"));
+    }
+
+    #[test]
+    fn test_forcing_budget() {
+        let (puzzle, possible_values_map) = stalled(&".".repeat(81));
+        let layout = Rc::new(Layout::standard());
+
+        let mut budget = usize::MAX;
+        let chain = find_forcing_chain_within(&puzzle, &possible_values_map, &layout, &mut budget);
+        assert_eq!(chain, None);
+        assert!(usize::MAX - budget <= 2 * 81 + 9);
+
+        let mut budget = 10;
+        let chain = find_forcing_chain_within(&puzzle, &possible_values_map, &layout, &mut budget);
+        assert_eq!(chain, None);
+        assert_eq!(budget, 0);
+    }
+
+    #[test]
+    fn test_describe_sized() {
+        let size = Size::new(4);
+        let chain = ForcingChain {
+            kind: ForcingKind::Cell(255),
+            branches: vec![
+                Branch { index: 255, value: 1, contradiction: true },
+                Branch { index: 255, value: 2, contradiction: false }
+            ],
+            placements: vec![(254, 3)],
+        };
+        assert_eq!(
+            chain.describe(size),
+            "cell forcing chain on r16c16: r16c16=1 fails, r16c16=2 => r16c15=3"
+        );
+        assert_eq!(chain.kind().describe(Size::STANDARD), chain.kind().to_string());
+    }
+}
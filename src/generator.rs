@@ -0,0 +1,493 @@
+use std::time::{ Duration, Instant };
+
+use crate::{
+    block_of,
+    has_unique_solution,
+    rate,
+    rng::Rng,
+    vecu8_to_str,
+    Band,
+    Rating,
+    Technique,
+};
+
+/// Solved grids tried before giving up on a clue mask.
+const MASK_ATTEMPTS: usize = 100;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Symmetry {
+    #[default]
+    None,
+    Rotational180,
+    Rotational90,
+    /// Mirrored along the main diagonal.
+    Diagonal,
+    /// Mirrored top to bottom.
+    Horizontal,
+    /// Mirrored left to right.
+    Vertical,
+}
+
+impl Symmetry {
+    /// The cells which are given or left empty together with cell `i`.
+    pub fn orbit(&self, i: usize) -> Vec<usize> {
+        let (r, c) = (i / 9, i % 9);
+        let mut orbit = match self {
+            Symmetry::None => vec![i],
+            Symmetry::Rotational180 => vec![i, 80 - i],
+            Symmetry::Rotational90 =>
+                vec![i, c * 9 + (8 - r), (8 - r) * 9 + (8 - c), (8 - c) * 9 + r],
+            Symmetry::Diagonal => vec![i, c * 9 + r],
+            Symmetry::Horizontal => vec![i, (8 - r) * 9 + c],
+            Symmetry::Vertical => vec![i, r * 9 + (8 - c)],
+        };
+        orbit.sort();
+        orbit.dedup();
+        orbit
+    }
+}
+
+/// Where the clues of a generated puzzle may go.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClueLayout {
+    Symmetric(Symmetry),
+    /// Clues only on the cells marked `true`.
+    Mask(Vec<bool>),
+}
+
+impl Default for ClueLayout {
+    fn default() -> Self {
+        ClueLayout::Symmetric(Symmetry::None)
+    }
+}
+
+impl ClueLayout {
+    /// A mask of 81 characters, where '.' and '0' mark the cells to leave empty, or `None` if
+    /// it has another length.
+    pub fn from_mask(mask: &str) -> Option<Self> {
+        let mask: Vec<bool> = mask
+            .chars()
+            .map(|c| c != '.' && c != '0')
+            .collect();
+        if mask.len() != 81 {
+            return None;
+        }
+        Some(ClueLayout::Mask(mask))
+    }
+
+    /// Whether the layout covers the 81 cells of the grid.
+    fn fits(&self) -> bool {
+        match self {
+            ClueLayout::Symmetric(_) => true,
+            ClueLayout::Mask(mask) => mask.len() == 81,
+        }
+    }
+
+    fn allows(&self, i: usize) -> bool {
+        match self {
+            ClueLayout::Symmetric(_) => true,
+            ClueLayout::Mask(mask) => mask[i],
+        }
+    }
+
+    fn orbit(&self, i: usize) -> Vec<usize> {
+        match self {
+            ClueLayout::Symmetric(symmetry) => symmetry.orbit(i),
+            ClueLayout::Mask(_) => vec![i],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GeneratorOptions {
+    pub seed: u64,
+    /// Digging stops once the puzzle is down to this many clues. It may end with more when
+    /// no further clue can go without losing uniqueness.
+    pub target_clues: usize,
+    pub layout: ClueLayout,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratedPuzzle {
+    pub puzzle: String,
+    pub solution: String,
+}
+
+/// The difficulty a generated puzzle has to have.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DifficultyTarget {
+    /// A rating score within the inclusive range.
+    Score(f32, f32),
+    Band(Band),
+    /// Needs exactly this technique as its hardest one.
+    Hardest(Technique),
+    /// Needs every technique of `required`, and none outside `allowed` when it is given, e.g.
+    /// an X-Wing but nothing harder.
+    Techniques {
+        required: Vec<Technique>,
+        allowed: Option<Vec<Technique>>,
+    },
+}
+
+impl DifficultyTarget {
+    pub fn accepts(&self, rating: &Rating) -> bool {
+        match self {
+            DifficultyTarget::Score(min, max) => *min <= rating.score && rating.score <= *max,
+            DifficultyTarget::Band(band) => rating.band == *band,
+            DifficultyTarget::Hardest(technique) => rating.hardest == *technique,
+            DifficultyTarget::Techniques { required, allowed } => {
+                required.iter().all(|t| rating.techniques.contains(t)) &&
+                    allowed.as_ref().is_none_or(|allowed| {
+                        rating.techniques.iter().all(|t| allowed.contains(t))
+                    })
+            }
+        }
+    }
+}
+
+/// How long generation may keep trying before giving up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Budget {
+    pub attempts: usize,
+    pub time: Option<Duration>,
+}
+
+impl Default for Budget {
+    fn default() -> Self {
+        Budget { attempts: 100, time: None }
+    }
+}
+
+fn fill(grid: &mut [u8], used: &mut [[u16; 9]; 3], index: usize, rng: &mut Rng) -> bool {
+    if index == grid.len() {
+        return true;
+    }
+    let (r, c, b) = (index / 9, index % 9, block_of(index));
+
+    let mut values: Vec<u8> = (1..=9).collect();
+    rng.shuffle(&mut values);
+    for v in values {
+        let bit = 1 << v;
+        if (used[0][r] | used[1][c] | used[2][b]) & bit != 0 {
+            continue;
+        }
+        grid[index] = v;
+        used[0][r] |= bit;
+        used[1][c] |= bit;
+        used[2][b] |= bit;
+        if fill(grid, used, index + 1, rng) {
+            return true;
+        }
+        used[0][r] &= !bit;
+        used[1][c] &= !bit;
+        used[2][b] &= !bit;
+    }
+    grid[index] = 0;
+
+    false
+}
+
+fn random_solution(rng: &mut Rng) -> Vec<u8> {
+    let mut grid = vec![0; 81];
+    let mut used = [[0u16; 9]; 3];
+    fill(&mut grid, &mut used, 0, rng);
+    grid
+}
+
+/// A random solved grid. The same seed always gives the same grid.
+pub fn generate_solution(seed: u64) -> String {
+    vecu8_to_str(&random_solution(&mut Rng::new(seed)))
+}
+
+fn dig(puzzle: &mut [u8], solution: &[u8], options: &GeneratorOptions, rng: &mut Rng) {
+    let mut cells: Vec<usize> = (0..puzzle.len()).collect();
+    rng.shuffle(&mut cells);
+    let mut clues = puzzle
+        .iter()
+        .filter(|&&v| v != 0)
+        .count();
+    for i in cells {
+        if clues <= options.target_clues {
+            break;
+        }
+        let orbit = options.layout.orbit(i);
+        if puzzle[i] == 0 || clues - orbit.len() < options.target_clues {
+            continue;
+        }
+        orbit.iter().for_each(|&j| {
+            puzzle[j] = 0;
+        });
+        if has_unique_solution(&vecu8_to_str(puzzle)) {
+            clues -= orbit.len();
+        } else {
+            orbit.iter().for_each(|&j| {
+                puzzle[j] = solution[j];
+            });
+        }
+    }
+}
+
+/// Digs holes into a random solved grid, in random order, keeping only the removals after
+/// which the puzzle still has a unique solution. Symmetric cells are removed together, and
+/// with a mask the digging starts from the masked cells only.
+///
+/// # Panics
+///
+/// If the clue layout is a mask which `try_generate_puzzle` cannot fill.
+pub fn generate_puzzle(options: &GeneratorOptions) -> GeneratedPuzzle {
+    try_generate_puzzle(options).expect("no unique puzzle fits the clue mask")
+}
+
+/// Same as `generate_puzzle`, returning `None` if the mask does not cover the 81 cells or no
+/// solved grid gives a unique puzzle on it.
+pub fn try_generate_puzzle(options: &GeneratorOptions) -> Option<GeneratedPuzzle> {
+    if !options.layout.fits() {
+        return None;
+    }
+    let mut rng = Rng::new(options.seed);
+
+    for _ in 0..MASK_ATTEMPTS {
+        let solution = random_solution(&mut rng);
+        let mut puzzle: Vec<u8> = (0..solution.len())
+            .map(|i| if options.layout.allows(i) { solution[i] } else { 0 })
+            .collect();
+        if !has_unique_solution(&vecu8_to_str(&puzzle)) {
+            continue;
+        }
+
+        dig(&mut puzzle, &solution, options, &mut rng);
+        return Some(GeneratedPuzzle {
+            puzzle: vecu8_to_str(&puzzle),
+            solution: vecu8_to_str(&solution),
+        });
+    }
+
+    None
+}
+
+/// Generates puzzles until one is rated as the target asks, or the budget runs out. Every
+/// attempt is seeded from `options.seed`, so the result is reproducible unless a time limit
+/// cuts the search short.
+pub fn generate_puzzle_with_difficulty(
+    options: &GeneratorOptions,
+    target: &DifficultyTarget,
+    budget: &Budget
+) -> Option<(GeneratedPuzzle, Rating)> {
+    let start = Instant::now();
+    let mut seeds = Rng::new(options.seed);
+
+    for _ in 0..budget.attempts {
+        if budget.time.is_some_and(|time| start.elapsed() >= time) {
+            break;
+        }
+        let attempt = GeneratorOptions { seed: seeds.next_u64(), ..options.clone() };
+        let generated = match try_generate_puzzle(&attempt) {
+            Some(generated) => generated,
+            None => {
+                continue;
+            }
+        };
+        if let Some(rating) = rate(&generated.puzzle) {
+            if target.accepts(&rating) {
+                return Some((generated, rating));
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ count_solutions, has_conflicts, rating::difficulty, solution_path, str_to_vecu8 };
+
+    use super::*;
+
+    #[test]
+    fn test_generate_solution() {
+        for seed in 0..20 {
+            let solution = generate_solution(seed);
+            assert!(!solution.contains('.'));
+            assert!(!has_conflicts(&str_to_vecu8(&solution)));
+        }
+        assert_eq!(generate_solution(42), generate_solution(42));
+        assert_ne!(generate_solution(42), generate_solution(43));
+    }
+
+    #[test]
+    fn test_generate_solution_is_stable() {
+        // pinned so that a change of the algorithm does not go unnoticed
+        assert_eq!(
+            generate_solution(2024),
+            "652879134819463527743215689431528976987641253526397418398156742265734891174982365"
+        );
+    }
+
+    #[test]
+    fn test_generate_puzzle() {
+        let options = GeneratorOptions { seed: 7, target_clues: 30, ..Default::default() };
+        let generated = generate_puzzle(&options);
+        let clues = generated.puzzle
+            .chars()
+            .filter(|&c| c != '.')
+            .count();
+
+        assert_eq!(clues, 30);
+        assert_eq!(count_solutions(&generated.puzzle, 2), 1);
+        assert!(
+            generated.puzzle
+                .chars()
+                .zip(generated.solution.chars())
+                .all(|(p, s)| p == '.' || p == s)
+        );
+        assert_eq!(generate_puzzle(&options), generated);
+        assert_eq!(try_generate_puzzle(&options), Some(generated));
+    }
+
+    #[test]
+    fn test_generate_puzzle_as_few_clues_as_possible() {
+        let options = GeneratorOptions { seed: 1, ..Default::default() };
+        let generated = generate_puzzle(&options);
+        let clues = generated.puzzle
+            .chars()
+            .filter(|&c| c != '.')
+            .count();
+
+        assert!(clues < 30);
+        assert_eq!(count_solutions(&generated.puzzle, 2), 1);
+    }
+
+    #[test]
+    fn test_generate_puzzle_with_difficulty() {
+        let options = GeneratorOptions::default();
+
+        let target = DifficultyTarget::Hardest(Technique::XWing);
+        let (generated, rating) = generate_puzzle_with_difficulty(
+            &options,
+            &target,
+            &Budget::default()
+        ).unwrap();
+        assert_eq!(rating.hardest, Technique::XWing);
+        assert_eq!(rate(&generated.puzzle), Some(rating));
+
+        let target = DifficultyTarget::Score(2.5, 2.9);
+        let (_, rating) = generate_puzzle_with_difficulty(
+            &options,
+            &target,
+            &Budget::default()
+        ).unwrap();
+        assert!(rating.band == Band::Medium);
+
+        // an X-Wing, and nothing rated above it
+        let target = DifficultyTarget::Techniques {
+            required: vec![Technique::XWing],
+            allowed: Some(
+                vec![
+                    Technique::HiddenSingle,
+                    Technique::NakedSingle,
+                    Technique::Pointing,
+                    Technique::Claiming,
+                    Technique::NakedPair,
+                    Technique::XWing
+                ]
+            ),
+        };
+        let (generated, _) = generate_puzzle_with_difficulty(
+            &options,
+            &target,
+            &Budget::default()
+        ).unwrap();
+        let steps = solution_path(&generated.puzzle).unwrap();
+        assert!(steps.iter().any(|step| step.technique == Technique::XWing));
+        assert!(steps.iter().all(|step| difficulty(step) <= 3.2));
+
+        let target = DifficultyTarget::Techniques {
+            required: vec![Technique::Guess],
+            allowed: Some(vec![Technique::HiddenSingle]),
+        };
+        let budget = Budget { attempts: 5, time: None };
+        assert_eq!(generate_puzzle_with_difficulty(&options, &target, &budget), None);
+
+        let budget = Budget { attempts: 1000, time: Some(Duration::ZERO) };
+        assert_eq!(generate_puzzle_with_difficulty(&options, &target, &budget), None);
+    }
+
+    #[test]
+    fn test_symmetry_orbit() {
+        assert_eq!(Symmetry::None.orbit(1), vec![1]);
+        assert_eq!(Symmetry::Rotational180.orbit(1), vec![1, 79]);
+        assert_eq!(Symmetry::Rotational90.orbit(1), vec![1, 17, 63, 79]);
+        assert_eq!(Symmetry::Rotational90.orbit(40), vec![40]);
+        assert_eq!(Symmetry::Diagonal.orbit(1), vec![1, 9]);
+        assert_eq!(Symmetry::Horizontal.orbit(1), vec![1, 73]);
+        assert_eq!(Symmetry::Vertical.orbit(1), vec![1, 7]);
+    }
+
+    #[test]
+    fn test_generate_symmetric_puzzle() {
+        for symmetry in [
+            Symmetry::Rotational180,
+            Symmetry::Rotational90,
+            Symmetry::Diagonal,
+            Symmetry::Horizontal,
+            Symmetry::Vertical,
+        ] {
+            let options = GeneratorOptions {
+                seed: 3,
+                layout: ClueLayout::Symmetric(symmetry),
+                ..Default::default()
+            };
+            let generated = generate_puzzle(&options);
+            let puzzle = generated.puzzle.as_bytes();
+
+            assert_eq!(count_solutions(&generated.puzzle, 2), 1);
+            for i in 0..81 {
+                let given = puzzle[i] != b'.';
+                assert!(
+                    symmetry
+                        .orbit(i)
+                        .iter()
+                        .all(|&j| (puzzle[j] != b'.') == given)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_generate_masked_puzzle() {
+        // everything but the middle block
+        let mask: String = (0..81)
+            .map(|i| if block_of(i) == 4 { '.' } else { 'x' })
+            .collect();
+        let options = GeneratorOptions {
+            seed: 5,
+            layout: ClueLayout::from_mask(&mask).unwrap(),
+            ..Default::default()
+        };
+        let generated = try_generate_puzzle(&options).unwrap();
+
+        assert_eq!(count_solutions(&generated.puzzle, 2), 1);
+        assert!(
+            generated.puzzle
+                .chars()
+                .zip(mask.chars())
+                .all(|(p, m)| p == '.' || m == 'x')
+        );
+
+        let mask = format!("{}{}", "x".repeat(16), ".".repeat(65));
+        let options = GeneratorOptions {
+            layout: ClueLayout::from_mask(&mask).unwrap(),
+            ..Default::default()
+        };
+        assert_eq!(try_generate_puzzle(&options), None);
+
+        // masks which miss cells or run past the grid
+        assert_eq!(ClueLayout::from_mask(&"x".repeat(80)), None);
+        assert_eq!(ClueLayout::from_mask(&"x".repeat(82)), None);
+        let options = GeneratorOptions {
+            layout: ClueLayout::Mask(vec![true; 9]),
+            ..Default::default()
+        };
+        assert_eq!(try_generate_puzzle(&options), None);
+    }
+}
@@ -1,13 +1,23 @@
 use std::{ cell::RefCell, collections::HashMap, rc::Rc };
 
-use crate::{ conjugate_block_index, conjugate_col_index, conjugate_row_index };
+use crate::{ constraint::{ all_digits, digits }, House, Layout, SolveStep };
+
+/// Outcome of running the singles until no task makes progress any more.
+#[derive(Debug)]
+pub enum Propagation {
+    Solved,
+    Stalled(Vec<GridTask>),
+    Contradiction,
+}
 
 #[derive(Debug)]
 pub struct GridTask {
     puzzle: Rc<RefCell<Vec<u8>>>,
     index: usize,
+    layout: Rc<Layout>,
     done: bool,
     updated: bool,
+    step: Option<SolveStep>,
     possible_values_map: Rc<RefCell<HashMap<usize, Rc<Vec<u8>>>>>,
 }
 
@@ -16,12 +26,24 @@ impl GridTask {
         puzzle: Rc<RefCell<Vec<u8>>>,
         index: usize,
         possible_values_map: Rc<RefCell<HashMap<usize, Rc<Vec<u8>>>>>
+    ) -> Self {
+        GridTask::new_in(puzzle, index, possible_values_map, Rc::new(Layout::standard()))
+    }
+
+    /// Same as `new` for a cell of a puzzle with the given layout.
+    pub fn new_in(
+        puzzle: Rc<RefCell<Vec<u8>>>,
+        index: usize,
+        possible_values_map: Rc<RefCell<HashMap<usize, Rc<Vec<u8>>>>>,
+        layout: Rc<Layout>
     ) -> Self {
         GridTask {
             puzzle,
             index,
+            layout,
             done: false,
             updated: false,
+            step: None,
             possible_values_map,
         }
     }
@@ -29,6 +51,15 @@ impl GridTask {
     pub fn generate_tasks(
         puzzle: Rc<RefCell<Vec<u8>>>,
         possible_values_map: Rc<RefCell<HashMap<usize, Rc<Vec<u8>>>>>
+    ) -> Vec<GridTask> {
+        GridTask::generate_tasks_in(puzzle, possible_values_map, Rc::new(Layout::standard()))
+    }
+
+    /// Same as `generate_tasks` for a puzzle with the given layout.
+    pub fn generate_tasks_in(
+        puzzle: Rc<RefCell<Vec<u8>>>,
+        possible_values_map: Rc<RefCell<HashMap<usize, Rc<Vec<u8>>>>>,
+        layout: Rc<Layout>
     ) -> Vec<GridTask> {
         let mut todo: Vec<GridTask> = Vec::new();
 
@@ -36,46 +67,58 @@ impl GridTask {
             .borrow()
             .iter()
             .enumerate()
-            .for_each(|(index, num)| {
-                match num {
-                    0 => {
-                        todo.push(
-                            GridTask::new(
-                                Rc::clone(&puzzle),
-                                index,
-                                Rc::clone(&possible_values_map)
-                            )
+            .for_each(|(index, &num)| {
+                if num == 0 {
+                    todo.push(
+                        GridTask::new_in(
+                            Rc::clone(&puzzle),
+                            index,
+                            Rc::clone(&possible_values_map),
+                            Rc::clone(&layout)
                         )
-                    }
-                    _ => (),
+                    )
                 }
             });
 
         todo
     }
 
+    /// Fills `possible_values_map` for every empty cell without touching the puzzle.
+    pub fn calculate_all_possible_values(
+        puzzle: Rc<RefCell<Vec<u8>>>,
+        possible_values_map: Rc<RefCell<HashMap<usize, Rc<Vec<u8>>>>>,
+        layout: Rc<Layout>
+    ) {
+        GridTask::generate_tasks_in(puzzle, possible_values_map, layout)
+            .iter()
+            .for_each(|t| {
+                t.calculate_possible_values();
+            });
+    }
+
     pub fn possible_values(&self) -> Rc<Vec<u8>> {
         Rc::clone(self.possible_values_map.borrow().get(&self.index).unwrap())
     }
 
     fn calculate_possible_values(&self) -> Rc<Vec<u8>> {
         let mut possible_values = vec![];
-        let mut possible_values_flag = vec![true; 10];
-        let closure_update_flags = |i: &usize, possible_values_flag: &mut [bool]| {
-            possible_values_flag[self.puzzle.borrow()[*i] as usize] = false;
-        };
-
-        conjugate_row_index(self.index)
-            .iter()
-            .for_each(|i| closure_update_flags(i, &mut possible_values_flag));
-        conjugate_col_index(self.index)
-            .iter()
-            .for_each(|i| closure_update_flags(i, &mut possible_values_flag));
-        conjugate_block_index(self.index)
+        let mut possible_values_flag = vec![true; self.layout.size().side() + 1];
+        // values already ruled out, by an earlier run or by other techniques, stay out
+        if let Some(vs) = self.possible_values_map.borrow().get(&self.index) {
+            possible_values_flag.fill(false);
+            vs.iter().for_each(|&v| {
+                possible_values_flag[v as usize] = true;
+            });
+        }
+        let puzzle = self.puzzle.borrow();
+        self.layout
+            .peers(self.index)
             .iter()
-            .for_each(|i| closure_update_flags(i, &mut possible_values_flag));
+            .for_each(|&i| {
+                possible_values_flag[puzzle[i] as usize] = false;
+            });
 
-        possible_values_flag[1..10]
+        possible_values_flag[1..]
             .iter()
             .enumerate()
             .for_each(|(i, flag)| {
@@ -90,27 +133,27 @@ impl GridTask {
         values_rc
     }
 
-    fn calculate_exclusive_possible_values(&self) -> u8 {
-        for indexs in vec![
-            conjugate_row_index(self.index),
-            conjugate_col_index(self.index),
-            conjugate_block_index(self.index)
-        ] {
-            let mut possible_values_flag: Vec<bool> = vec![false; 10];
-            self.possible_values()
-                .iter()
-                .for_each(|&i| {
-                    possible_values_flag[i as usize] = true;
-                });
+    fn calculate_exclusive_possible_values(&self) -> Option<(u8, House)> {
+        let puzzle = self.puzzle.borrow();
+        let possible_values_map = self.possible_values_map.borrow();
+        let possible_values = &possible_values_map[&self.index];
+        // only a house holding every digit needs one of them in each of its cells
+        let houses = self.layout
+            .houses_of(self.index)
+            .iter()
+            .filter(|&&h| self.layout.is_complete(h))
+            .map(|&h| &self.layout.houses()[h]);
+        for (house, indexs) in houses {
+            let mut possible_values_flag: Vec<bool> = vec![false; self.layout.size().side() + 1];
+            possible_values.iter().for_each(|&i| {
+                possible_values_flag[i as usize] = true;
+            });
 
             indexs
-                .into_iter()
-                .filter(|&i| {
-                    let puzzle_rc = Rc::clone(&self.puzzle);
-                    i != self.index && puzzle_rc.borrow()[i] == 0
-                })
+                .iter()
+                .filter(|&&i| i != self.index && puzzle[i] == 0)
                 .for_each(|i| {
-                    if let Some(vs) = self.possible_values_map.borrow().get(&i) {
+                    if let Some(vs) = possible_values_map.get(i) {
                         vs.iter().for_each(|&v| {
                             possible_values_flag[v as usize] = false;
                         })
@@ -128,11 +171,11 @@ impl GridTask {
                     v
                 });
             if exclusive_possible_values.len() == 1 {
-                return exclusive_possible_values[0];
+                return Some((exclusive_possible_values[0], *house));
             }
         }
 
-        0
+        None
     }
 
     pub fn done(&self) -> bool {
@@ -151,19 +194,26 @@ impl GridTask {
         self.index
     }
 
+    /// Why the cell was filled, once the task is updated.
+    pub fn step(&self) -> Option<&SolveStep> {
+        self.step.as_ref()
+    }
+
     pub fn run(&mut self) {
         let possible_values = self.calculate_possible_values();
 
-        if possible_values.len() == 1 {
-            self.puzzle.borrow_mut()[self.index] = possible_values[0];
+        // a hidden single is the easier find, so it is preferred when both apply
+        if let Some((value, house)) = self.calculate_exclusive_possible_values() {
+            self.puzzle.borrow_mut()[self.index] = value;
+            self.step = Some(SolveStep::hidden_single(self.index, value, house));
             self.updated = true;
             self.done = true;
             return;
         }
 
-        let exclusive_possible_value = self.calculate_exclusive_possible_values();
-        if exclusive_possible_value != 0 {
-            self.puzzle.borrow_mut()[self.index] = exclusive_possible_value;
+        if possible_values.len() == 1 {
+            self.puzzle.borrow_mut()[self.index] = possible_values[0];
+            self.step = Some(SolveStep::naked_single(self.index, possible_values[0], &self.layout));
             self.updated = true;
             self.done = true;
             return;
@@ -171,10 +221,109 @@ impl GridTask {
 
         self.done = true;
     }
+
+    /// Runs every task of the puzzle until all of them are done without filling a cell,
+    /// returning the tasks that are still open.
+    pub fn propagate(
+        puzzle: Rc<RefCell<Vec<u8>>>,
+        possible_values_map: Rc<RefCell<HashMap<usize, Rc<Vec<u8>>>>>,
+        layout: Rc<Layout>
+    ) -> Propagation {
+        GridTask::propagate_logged(puzzle, possible_values_map, layout, &mut vec![])
+    }
+
+    /// Same as `propagate`, appending the step of every filled cell to `steps`.
+    pub fn propagate_logged(
+        puzzle: Rc<RefCell<Vec<u8>>>,
+        possible_values_map: Rc<RefCell<HashMap<usize, Rc<Vec<u8>>>>>,
+        layout: Rc<Layout>,
+        steps: &mut Vec<SolveStep>
+    ) -> Propagation {
+        let mut tasks = GridTask::generate_tasks_in(
+            Rc::clone(&puzzle),
+            Rc::clone(&possible_values_map),
+            Rc::clone(&layout)
+        );
+
+        loop {
+            // the constraints beyond the houses narrow the possible values before every round
+            let applied = GridTask::apply_constraints(
+                &puzzle.borrow(),
+                &mut possible_values_map.borrow_mut(),
+                &layout
+            );
+            match applied {
+                None => {
+                    return Propagation::Contradiction;
+                }
+                Some(true) => tasks.iter_mut().for_each(|t| t.reset_done()),
+                Some(false) => (),
+            }
+            if tasks.is_empty() {
+                return Propagation::Solved;
+            }
+            if tasks.iter().all(|t| t.done()) {
+                return Propagation::Stalled(tasks);
+            }
+
+            tasks.iter_mut().for_each(|t| t.run());
+
+            let tasks_count_backup = tasks.len();
+            steps.extend(tasks.iter().filter_map(|t| t.step().cloned()));
+            tasks.retain(|t| !t.updated());
+
+            if tasks.iter().any(|t| t.possible_values().is_empty()) {
+                return Propagation::Contradiction;
+            }
+
+            if tasks.len() != tasks_count_backup {
+                tasks.iter_mut().for_each(|t| t.reset_done());
+            }
+        }
+    }
+
+    /// Removes the possible values the constraints of the layout rule out. Returns `None` if
+    /// some constraint cannot be met any more, else whether any possible values changed.
+    pub(crate) fn apply_constraints(
+        puzzle: &[u8],
+        possible_values_map: &mut HashMap<usize, Rc<Vec<u8>>>,
+        layout: &Layout
+    ) -> Option<bool> {
+        let all = all_digits(layout.size().side());
+        let mut changed = false;
+        for (c, constraint) in layout.constraints().iter().enumerate() {
+            let mask_of = |i: usize| {
+                match puzzle[i] {
+                    0 =>
+                        possible_values_map
+                            .get(&i)
+                            .map_or(all, |vs| vs.iter().fold(0, |mask, &v| mask | (1 << v))),
+                    v => 1 << v,
+                }
+            };
+            let before: Vec<u32> = constraint
+                .cells()
+                .iter()
+                .map(|&i| mask_of(i))
+                .collect();
+            let after = layout.prune_constraint(c, mask_of);
+
+            for ((&i, before), after) in constraint.cells().iter().zip(before).zip(after) {
+                if after == 0 {
+                    return None;
+                }
+                if after != before {
+                    possible_values_map.insert(i, Rc::new(digits(after).collect()));
+                    changed = true;
+                }
+            }
+        }
+        Some(changed)
+    }
 }
 #[cfg(test)]
 mod tests {
-    use crate::str_to_vecu8;
+    use crate::{ str_to_vecu8, str_to_vecu8_sized, vecu8_to_str, vecu8_to_str_sized, Size };
 
     use super::*;
 
@@ -192,4 +341,43 @@ mod tests {
             expected_output_len
         )
     }
+
+    #[test]
+    fn test_propagate() {
+        let input =
+            ".5..83.17...1..4..3.4..56.8....3...9.9.8245....6....7...9....5...729..861.36.72.4";
+        let expected_output =
+            "652483917978162435314975628825736149791824563436519872269348751547291386183657294";
+        let puzzle = Rc::new(RefCell::new(str_to_vecu8(input)));
+
+        assert!(
+            matches!(
+                GridTask::propagate(
+                    Rc::clone(&puzzle),
+                    Rc::new(RefCell::new(HashMap::<usize, Rc<Vec<u8>>>::new())),
+                    Rc::new(Layout::standard())
+                ),
+                Propagation::Solved
+            )
+        );
+        assert_eq!(vecu8_to_str(&puzzle.borrow()), expected_output);
+    }
+
+    #[test]
+    fn test_propagate_sized() {
+        let size = Size::new(2);
+        let puzzle = Rc::new(RefCell::new(str_to_vecu8_sized("1.3..4.2.1..4..1", size)));
+
+        assert!(
+            matches!(
+                GridTask::propagate(
+                    Rc::clone(&puzzle),
+                    Rc::new(RefCell::new(HashMap::<usize, Rc<Vec<u8>>>::new())),
+                    Rc::new(Layout::new(size))
+                ),
+                Propagation::Solved
+            )
+        );
+        assert_eq!(vecu8_to_str_sized(&puzzle.borrow(), size), "1234341221434321");
+    }
 }
@@ -0,0 +1,204 @@
+use std::{ cell::RefCell, collections::HashMap, rc::Rc };
+
+use crate::{
+    find_elimination_in,
+    find_forcing_chain_in,
+    strategies::{ candidate_masks, find_hidden_single, find_naked_single, is_line },
+    GridTask,
+    House,
+    Layout,
+    Size,
+    SolveStep,
+    Technique,
+};
+
+/// The easiest deduction available on a grid, meant to be disclosed bit by bit: first the
+/// house to look at, then the technique, then the step itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hint {
+    step: SolveStep,
+    house: House,
+    size: Size,
+}
+
+impl Hint {
+    /// The house to look at: the one the step works in, or for a step about a single cell
+    /// the block, or whichever house takes its place in the layout, holding the cell.
+    fn new(step: SolveStep, layout: &Layout) -> Self {
+        let house = match step.technique {
+            Technique::NakedSingle | Technique::CellForcingChain | Technique::Guess => {
+                let houses: Vec<House> = layout
+                    .houses_of(step.cells[0])
+                    .iter()
+                    .map(|&h| layout.houses()[h].0)
+                    .collect();
+                houses
+                    .iter()
+                    .copied()
+                    .find(|&house| !is_line(house))
+                    .unwrap_or(houses[0])
+            }
+            _ => step.houses[0],
+        };
+        Hint { step, house, size: layout.size() }
+    }
+
+    pub fn house(&self) -> House {
+        self.house
+    }
+
+    pub fn technique(&self) -> Technique {
+        self.step.technique
+    }
+
+    pub fn step(&self) -> &SolveStep {
+        &self.step
+    }
+
+    /// The hint as text, saying more with every level from 1 to 3.
+    pub fn disclose(&self, level: usize) -> String {
+        match level {
+            0 | 1 => format!("Look at {}.", self.house()),
+            2 => format!("Look at {} for a {}.", self.house(), self.technique()),
+            _ => self.step.describe(self.size),
+        }
+    }
+}
+
+/// Finds the easiest next step for a partially filled grid. Pencil marks, if given, are taken
+/// as the player's candidates for the cells they cover, so steps the player already took are
+/// not hinted again. Returns `None` if the grid is broken, a pencil mark is not a digit of a
+/// cell of the grid or no logical step is left.
+pub fn hint(puzzle: &str, pencil_marks: Option<&HashMap<usize, Vec<u8>>>) -> Option<Hint> {
+    hint_in(puzzle, &Layout::standard(), pencil_marks)
+}
+
+/// Same as `hint` for a puzzle with the given layout, whose houses and constraints every
+/// technique follows.
+pub fn hint_in(
+    puzzle: &str,
+    layout: &Layout,
+    pencil_marks: Option<&HashMap<usize, Vec<u8>>>
+) -> Option<Hint> {
+    let puzzle_vec = layout.parse(puzzle)?;
+    let size = layout.size();
+    let marks_fit = |marks: &HashMap<usize, Vec<u8>>| {
+        marks.iter().all(|(&i, vs)| {
+            i < size.cells() && vs.iter().all(|&v| (1..=size.side()).contains(&(v as usize)))
+        })
+    };
+    if !pencil_marks.is_none_or(marks_fit) {
+        return None;
+    }
+    let layout = Rc::new(layout.clone());
+    let puzzle_vec = Rc::new(RefCell::new(puzzle_vec));
+    let possible_values_map: HashMap<usize, Rc<Vec<u8>>> = pencil_marks
+        .map(|marks| {
+            marks
+                .iter()
+                .map(|(&i, vs)| (i, Rc::new(vs.clone())))
+                .collect()
+        })
+        .unwrap_or_default();
+    let possible_values_map = Rc::new(RefCell::new(possible_values_map));
+    GridTask::calculate_all_possible_values(
+        Rc::clone(&puzzle_vec),
+        Rc::clone(&possible_values_map),
+        Rc::clone(&layout)
+    );
+
+    let puzzle = puzzle_vec.borrow();
+    let mut possible_values_map = possible_values_map.borrow_mut();
+    // the constraints beyond the houses narrow the candidates as they do in `propagate`
+    while GridTask::apply_constraints(&puzzle, &mut possible_values_map, &layout)? {}
+    let candidates = candidate_masks(&puzzle, &possible_values_map);
+    if (0..puzzle.len()).any(|i| puzzle[i] == 0 && candidates[i] == 0) {
+        return None;
+    }
+
+    let step = find_hidden_single(&puzzle, &candidates, &layout)
+        .or_else(|| find_naked_single(&puzzle, &candidates, &layout))
+        .or_else(|| find_elimination_in(&puzzle, &possible_values_map, &layout))
+        .or_else(|| {
+            find_forcing_chain_in(&puzzle, &possible_values_map, &layout)
+                .filter(|chain| !chain.is_contradiction())
+                .map(|chain| SolveStep::from(&chain))
+        });
+
+    step.map(|step| Hint::new(step, &layout))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse_thermometers;
+
+    use super::*;
+
+    #[test]
+    fn test_hint() {
+        let input =
+            ".5..83.17...1..4..3.4..56.8....3...9.9.8245....6....7...9....5...729..861.36.72.4";
+
+        let hint = hint(input, None).unwrap();
+        assert_eq!(hint.technique(), Technique::HiddenSingle);
+        assert_eq!(hint.disclose(1), format!("Look at {}.", hint.house()));
+        assert!(hint.disclose(2).ends_with("for a hidden single."));
+        assert_eq!(hint.disclose(3), hint.step().to_string());
+    }
+
+    #[test]
+    fn test_hint_sized() {
+        let layout = Layout::new(Size::new(2));
+        let hint = hint_in("1.3..4.2.1..4..1", &layout, None).unwrap();
+        let (i, v) = hint.step().placements[0];
+        assert!(layout.indexes(hint.house()).unwrap().contains(&i));
+        assert!(hint.disclose(3).contains(&format!("r{}c{}={}", i / 4 + 1, (i % 4) + 1, v)));
+
+        assert_eq!(hint_in("1.3..4.2.1..4..", &layout, None), None);
+    }
+
+    #[test]
+    fn test_hint_with_constraints() {
+        // a thermometer along the top row leaves one digit for each of its cells
+        let thermometers = parse_thermometers(
+            "r1c1 r1c2 r1c3 r1c4 r1c5 r1c6 r1c7 r1c8 r1c9",
+            Size::STANDARD
+        ).unwrap();
+        let layout = Layout::thermo(Size::STANDARD, &thermometers);
+        let input = ".".repeat(81);
+        assert_eq!(hint(&input, None), None);
+
+        let hint = hint_in(&input, &layout, None).unwrap();
+        assert!(matches!(hint.technique(), Technique::HiddenSingle | Technique::NakedSingle));
+        let (i, v) = hint.step().placements[0];
+        assert!(i < 9 && usize::from(v) == i + 1);
+    }
+
+    #[test]
+    fn test_hint_with_pencil_marks() {
+        let input = ".".repeat(81);
+        // 5 left in the top row of block 1 only, as the player marked it
+        let pencil_marks: HashMap<usize, Vec<u8>> = [9, 10, 11, 18, 19, 20]
+            .into_iter()
+            .map(|i| (i, vec![1, 2, 3, 4, 6, 7, 8, 9]))
+            .collect();
+
+        let hint = hint(&input, Some(&pencil_marks)).unwrap();
+        assert_eq!(hint.technique(), Technique::Pointing);
+        assert_eq!(hint.house(), House::Block(0));
+    }
+
+    #[test]
+    fn test_hint_broken_grid() {
+        let input = format!("11{}", ".".repeat(79));
+
+        assert_eq!(hint(&input, None), None);
+
+        // marks which are no digit, or of no cell, are refused
+        let input = ".".repeat(81);
+        for (i, v) in [(0, 0), (0, 10), (0, 16), (81, 1)] {
+            let pencil_marks = HashMap::from([(i, vec![1, v])]);
+            assert_eq!(hint(&input, Some(&pencil_marks)), None);
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use std::fmt;
+
+use crate::Size;
+
+/// A row, column or block, i.e. a group of cells which must contain every digit once, or a
+/// group whose cells are given by a `Layout`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum House {
+    Row(usize),
+    Col(usize),
+    Block(usize),
+    /// The main diagonal from the top left is 0, the anti-diagonal from the top right is 1.
+    Diagonal(usize),
+    /// A jigsaw region, numbered in the order the regions first appear.
+    Region(usize),
+    /// A killer cage, numbered in the order the cages were given.
+    Cage(usize),
+    /// A Windoku window, numbered row by row over the grid of windows described at
+    /// `window_lines`, so that the four drawn windows of a 9×9 are 0, 1, 3 and 4 and the others
+    /// are the phantom windows they imply.
+    Window(usize),
+    Group(usize),
+}
+
+/// The rows, or columns, of the windows in line `g` of the grid of windows of a board of
+/// square boxes of `b` cells. The first `b - 1` lines are the drawn windows, one cell apart
+/// and from the border, and the last one gathers the lines left between them, e.g. 1-3, 5-7
+/// and then 0, 4 and 8 on a 9×9.
+fn window_lines(g: usize, b: usize) -> Vec<usize> {
+    if g + 1 < b {
+        (1..=b).map(|k| g * (b + 1) + k).collect()
+    } else {
+        (0..b).map(|k| k * (b + 1)).collect()
+    }
+}
+
+impl House {
+    pub fn all() -> Vec<House> {
+        House::all_sized(Size::STANDARD)
+    }
+
+    pub fn all_sized(size: Size) -> Vec<House> {
+        let side = size.side();
+        (0..side)
+            .map(House::Row)
+            .chain((0..side).map(House::Col))
+            .chain((0..side).map(House::Block))
+            .collect()
+    }
+
+    /// The row, column and block of a cell, in this order.
+    pub fn containing(index: usize) -> [House; 3] {
+        House::containing_sized(index, Size::STANDARD)
+    }
+
+    pub fn containing_sized(index: usize, size: Size) -> [House; 3] {
+        [
+            House::Row(size.row_of(index)),
+            House::Col(size.col_of(index)),
+            House::Block(size.block_of(index)),
+        ]
+    }
+
+    pub fn indexes(&self) -> Option<Vec<usize>> {
+        self.indexes_sized(Size::STANDARD)
+    }
+
+    /// The cells of the house on a board of the given size, or `None` for the houses whose
+    /// cells are only known to their `Layout`, for a house beyond the board and for windows on
+    /// a board of boxes which are not square.
+    pub fn indexes_sized(&self, size: Size) -> Option<Vec<usize>> {
+        let side = size.side();
+        match *self {
+            House::Row(r) if r < side => Some(size.conjugate_row_index(r * side)),
+            House::Col(c) if c < side => Some(size.conjugate_col_index(c)),
+            House::Block(b) if b < side => Some(size.conjugate_block_index(size.block_head(b))),
+            House::Diagonal(0) => Some((0..side).map(|k| k * (side + 1)).collect()),
+            House::Diagonal(1) => Some((1..=side).map(|k| k * (side - 1)).collect()),
+            House::Window(w) if w < side && size.box_width() == size.box_height() => {
+                let b = size.box_width();
+                let cols = window_lines(w % b, b);
+                let cells = window_lines(w / b, b)
+                    .into_iter()
+                    .flat_map(|r| cols.iter().map(move |&c| r * side + c))
+                    .collect();
+                Some(cells)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for House {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            House::Row(r) => write!(f, "row {}", r + 1),
+            House::Col(c) => write!(f, "column {}", c + 1),
+            House::Block(b) => write!(f, "block {}", b + 1),
+            House::Diagonal(0) => write!(f, "main diagonal"),
+            House::Diagonal(_) => write!(f, "anti-diagonal"),
+            House::Region(r) => write!(f, "region {}", r + 1),
+            House::Cage(c) => write!(f, "cage {}", c + 1),
+            House::Window(w) => write!(f, "window {}", w + 1),
+            House::Group(g) => write!(f, "group {}", g + 1),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_house_indexes() {
+        assert_eq!(House::Row(8).indexes(), Some(vec![72, 73, 74, 75, 76, 77, 78, 79, 80]));
+        assert_eq!(House::Col(5).indexes(), Some(vec![5, 14, 23, 32, 41, 50, 59, 68, 77]));
+        assert_eq!(House::Block(4).indexes(), Some(vec![30, 31, 32, 39, 40, 41, 48, 49, 50]));
+        assert_eq!(House::all().len(), 27);
+        assert_eq!(House::containing(41), [House::Row(4), House::Col(5), House::Block(4)]);
+        assert_eq!(House::Block(3).indexes_sized(Size::new(2)), Some(vec![10, 11, 14, 15]));
+        assert_eq!(House::all_sized(Size::new(4)).len(), 48);
+        assert_eq!(House::Diagonal(0).indexes_sized(Size::new(2)), Some(vec![0, 5, 10, 15]));
+        assert_eq!(House::Diagonal(1).indexes_sized(Size::new(2)), Some(vec![3, 6, 9, 12]));
+        assert_eq!(House::Window(0).indexes(), Some(vec![10, 11, 12, 19, 20, 21, 28, 29, 30]));
+        assert_eq!(House::Window(8).indexes(), Some(vec![0, 4, 8, 36, 40, 44, 72, 76, 80]));
+        assert_eq!(House::Window(2).indexes_sized(Size::new(2)), Some(vec![1, 2, 13, 14]));
+    }
+
+    #[test]
+    fn test_house_without_indexes() {
+        assert_eq!(House::Region(0).indexes(), None);
+        assert_eq!(House::Cage(0).indexes(), None);
+        assert_eq!(House::Group(0).indexes(), None);
+        assert_eq!(House::Row(9).indexes(), None);
+        assert_eq!(House::Diagonal(2).indexes(), None);
+        assert_eq!(House::Window(0).indexes_sized(Size::rectangular(3, 2)), None);
+    }
+}
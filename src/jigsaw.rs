@@ -0,0 +1,133 @@
+use crate::{ is_connected, Size };
+
+/// Reads a region map, one symbol per cell naming its region, e.g. 81 characters for a 9×9
+/// jigsaw. Regions are numbered in the order their symbols first appear. Returns `None` unless
+/// there are as many regions as digits, each of them connected and as large as a row.
+pub fn parse_regions(map: &str, size: Size) -> Option<Vec<usize>> {
+    let map: Vec<char> = map.chars().collect();
+    if map.len() != size.cells() {
+        return None;
+    }
+    let mut symbols: Vec<char> = vec![];
+    let regions: Vec<usize> = map
+        .iter()
+        .map(|&c| {
+            symbols
+                .iter()
+                .position(|&s| s == c)
+                .unwrap_or_else(|| {
+                    symbols.push(c);
+                    symbols.len() - 1
+                })
+        })
+        .collect();
+    if symbols.len() != size.side() {
+        return None;
+    }
+
+    let valid = (0..size.side()).all(|r| {
+        let cells: Vec<usize> = (0..size.cells()).filter(|&i| regions[i] == r).collect();
+        cells.len() == size.side() && is_connected(&cells, size)
+    });
+    if !valid {
+        return None;
+    }
+    Some(regions)
+}
+
+/// Draws a grid with walls between the cells of different regions, e.g. for the top half of
+/// a 4×4:
+///
+/// ```text
+/// +---+---+---+---+
+/// | 1   2   3 | 4 |
+/// +   +---+---+   +
+/// | 4 | 3   1   2 |
+/// ```
+pub fn render_jigsaw(sudoku: &str, size: Size, regions: &[usize]) -> String {
+    let side = size.side();
+    let cells: Vec<char> = sudoku.chars().collect();
+    let border = |r: usize| {
+        let mut line = String::new();
+        for c in 0..side {
+            let i = r * side + c;
+            let wall = r == 0 || r == side || regions[i] != regions[i - side];
+            line.push_str(if wall { "+---" } else { "+   " });
+        }
+        line.push_str("+\n");
+        line
+    };
+
+    let mut output = String::new();
+    for r in 0..side {
+        output.push_str(&border(r));
+        for c in 0..side {
+            let i = r * side + c;
+            let wall = c == 0 || regions[i] != regions[i - 1];
+            output.push_str(&format!("{} {} ", if wall { '|' } else { ' ' }, cells[i]));
+        }
+        output.push_str("|\n");
+    }
+    output.push_str(&border(side));
+    output
+}
+
+pub fn show_jigsaw(sudoku: &str, size: Size, regions: &[usize]) {
+    print!("{}", render_jigsaw(sudoku, size, regions));
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ has_unique_solution_in, solve_in, Layout };
+
+    use super::*;
+
+    const REGIONS: &str =
+        "112222333111222333111122333445555666444555666444455666777888999778888999777788999";
+    const PUZZLE: &str =
+        "....56...6.......53.4.8.1..2.5...4.8...9...........23.4...63..2..........9....67.";
+    const SOLUTION: &str =
+        "123456789689127345354789126235671498748932561916548237471863952567294813892315674";
+
+    #[test]
+    fn test_parse_regions() {
+        let regions = parse_regions(REGIONS, Size::STANDARD).unwrap();
+        assert_eq!(&regions[..9], &[0, 0, 1, 1, 1, 1, 2, 2, 2]);
+        assert_eq!(regions[80], 8);
+        assert_eq!(parse_regions("ABBBABBBCCCDCCDD", Size::new(2)), None);
+        assert_eq!(parse_regions("AABBAABBCCDDCCDE", Size::new(2)), None);
+        assert_eq!(parse_regions("AABBAABBCCDDCCD", Size::new(2)), None);
+        assert_eq!(parse_regions("ABBAABBACCDDCCDD", Size::new(2)), None);
+        assert!(parse_regions("AAABABBBCDDDCCCD", Size::new(2)).is_some());
+    }
+
+    #[test]
+    fn test_solve_jigsaw() {
+        let regions = parse_regions(REGIONS, Size::STANDARD).unwrap();
+        let layout = Layout::jigsaw(Size::STANDARD, &regions);
+        assert_eq!(solve_in(PUZZLE, &layout), Some(SOLUTION.to_string()));
+        assert!(has_unique_solution_in(PUZZLE, &layout));
+        assert!(layout.parse(SOLUTION).is_some());
+        assert_eq!(Layout::standard().parse(SOLUTION), None);
+    }
+
+    #[test]
+    fn test_render_jigsaw() {
+        let regions = parse_regions("AAABABBBCDDDCCCD", Size::new(2)).unwrap();
+        let expected_output =
+            "+---+---+---+---+
+| 1   2   3 | 4 |
++   +---+---+   +
+| 4 | 3   1   2 |
++---+---+---+---+
+| 2 | 1   4   3 |
++   +---+---+   +
+| 3   4   2 | 1 |
++---+---+---+---+
+";
+        assert_eq!(render_jigsaw("1234431221433421", Size::new(2), &regions), expected_output);
+
+        let regions = parse_regions(REGIONS, Size::STANDARD).unwrap();
+        show_jigsaw(PUZZLE, Size::STANDARD, &regions);
+    }
+}
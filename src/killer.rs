@@ -0,0 +1,211 @@
+use crate::{
+    constraint::{ all_digits, digits },
+    is_connected,
+    utils::{ clue_lines, parse_cells },
+    Size,
+};
+
+/// A killer cage: its digits add up to `sum` and do not repeat.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cage {
+    pub cells: Vec<usize>,
+    pub sum: u32,
+}
+
+/// Whether the cells can take distinct digits of `available`, one each.
+fn assignable(masks: &[u32], available: u32) -> bool {
+    match masks.split_first() {
+        None => true,
+        Some((&first, rest)) => {
+            digits(first & available).any(|v| assignable(rest, available & !(1 << v)))
+        }
+    }
+}
+
+/// Collects the sets of `count` distinct digits of `allowed`, from `lowest` up, adding up to
+/// `sum` once joined to `set`.
+fn combinations(count: usize, sum: u32, lowest: u8, allowed: u32, set: u32, out: &mut Vec<u32>) {
+    if count == 0 {
+        if sum == 0 {
+            out.push(set);
+        }
+        return;
+    }
+    for v in digits(allowed).filter(|&v| v >= lowest) {
+        if u32::from(v) > sum {
+            break;
+        }
+        combinations(count - 1, sum - u32::from(v), v + 1, allowed, set | (1 << v), out);
+    }
+}
+
+/// Keeps the candidates which are part of some set of distinct digits adding up to `sum`
+/// that can be spread over the cells of `masks`, one each.
+pub(crate) fn prune_sum(masks: &mut [u32], sum: u32, side: usize) {
+    let mut order: Vec<usize> = (0..masks.len()).collect();
+    order.sort_by_key(|&k| masks[k].count_ones());
+    let sorted: Vec<u32> = order
+        .iter()
+        .map(|&k| masks[k])
+        .collect();
+
+    let allowed = masks.iter().fold(0, |union, mask| union | mask) & all_digits(side);
+    let mut sets = vec![];
+    combinations(masks.len(), sum, 1, allowed, 0, &mut sets);
+
+    let mut support = vec![0u32; masks.len()];
+    for set in sets {
+        if !assignable(&sorted, set) {
+            continue;
+        }
+        for (k, &j) in order.iter().enumerate() {
+            let mut others = sorted.clone();
+            others.remove(k);
+            for v in digits(set & masks[j] & !support[j]) {
+                if assignable(&others, set & !(1 << v)) {
+                    support[j] |= 1 << v;
+                }
+            }
+        }
+    }
+
+    for (mask, support) in masks.iter_mut().zip(support) {
+        *mask &= support;
+    }
+}
+
+impl Cage {
+    /// Keeps the candidates which are part of some set of distinct digits adding up to the
+    /// sum that can be spread over the cells.
+    pub fn prune(&self, masks: &mut [u32], side: usize) {
+        prune_sum(masks, self.sum, side);
+    }
+}
+
+/// Reads killer cages, one per line of `clue_lines` as the sum, a colon and the cells of the
+/// cage, e.g. `15: r1c1 r1c2 r2c1`. Returns `None` unless every cage is connected, no cell is
+/// in two cages and every sum can be made of distinct digits.
+pub fn parse_cages(text: &str, size: Size) -> Option<Vec<Cage>> {
+    let mut cages: Vec<Cage> = vec![];
+    for line in clue_lines(text) {
+        let (sum, cells) = line.split_once(':')?;
+        let sum = sum.trim().parse::<u32>().ok()?;
+        let cells = parse_cells(cells, size)?;
+
+        let n = cells.len() as u32;
+        let side = size.side() as u32;
+        let (min, max) = ((n * (n + 1)) / 2, (n * (2 * side - n + 1)) / 2);
+        let taken = cages.iter().flat_map(|c| c.cells.iter());
+        if
+            cells.is_empty() ||
+            n > side ||
+            !(min..=max).contains(&sum) ||
+            (0..cells.len()).any(|k| cells[k + 1..].contains(&cells[k])) ||
+            taken.copied().any(|i| cells.contains(&i)) ||
+            !is_connected(&cells, size)
+        {
+            return None;
+        }
+        cages.push(Cage { cells, sum });
+    }
+    Some(cages)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{ cell::RefCell, collections::HashMap, rc::Rc };
+
+    use crate::{ has_unique_solution_in, solve_in, str_to_vecu8, GridTask, Layout, Propagation };
+
+    use super::*;
+
+    const CAGES: &str =
+        "13: r7c5 r8c5
+6: r9c1 r8c1
+25: r7c4 r7c3 r8c3 r7c2
+15: r5c1 r4c1
+15: r5c6 r5c5 r4c6 r4c5
+21: r4c9 r4c8 r3c9
+22: r1c4 r2c4 r2c3 r3c4
+18: r7c6 r7c7 r8c7
+13: r1c3 r1c2 r1c1
+15: r8c2 r9c2 r9c3
+15: r1c7 r1c8 r2c8 r3c8
+10: r9c6 r8c6 r9c7
+19: r2c6 r2c5 r1c6 r1c5
+14: r3c2 r3c3 r2c2 r4c2
+12: r2c1 r3c1
+15: r7c1 r6c1 r6c2 r6c3
+19: r3c6 r3c5 r3c7 r4c7
+12: r1c9 r2c9
+8: r9c4 r8c4
+3: r6c9 r7c9
+22: r7c8 r8c8 r9c8
+23: r5c2 r5c3 r5c4 r6c4
+18: r6c6 r6c5 r6c7
+21: r5c8 r5c9 r6c8 r5c7
+10: r8c9 r9c9
+5: r9c5
+4: r2c7
+12: r4c4 r4c3";
+    const SOLUTION: &str =
+        "652483917978162435314975628825736149791824563436519872269348751547291386183657294";
+
+    #[test]
+    fn test_parse_cages() {
+        let cages = parse_cages(CAGES, Size::STANDARD).unwrap();
+        assert_eq!(cages.len(), 28);
+        assert_eq!(cages[0], Cage { cells: vec![58, 67], sum: 13 });
+
+        let cages = parse_cages("# a comment\n\n10: r1c1, r1c2,r2c2\n", Size::STANDARD).unwrap();
+        assert_eq!(cages, vec![Cage { cells: vec![0, 1, 10], sum: 10 }]);
+
+        // unknown cell, repeated cell, impossible sum, overlapping and disconnected cages
+        assert_eq!(parse_cages("10: r1c1 r1c10", Size::STANDARD), None);
+        assert_eq!(parse_cages("10: r1c1 r1c1", Size::STANDARD), None);
+        assert_eq!(parse_cages("18: r1c1 r1c2", Size::STANDARD), None);
+        assert_eq!(parse_cages("10: r1c1 r1c2\n10: r1c2 r1c3", Size::STANDARD), None);
+        assert_eq!(parse_cages("10: r1c1 r1c3", Size::STANDARD), None);
+        assert_eq!(parse_cages("10 r1c1 r1c2", Size::STANDARD), None);
+    }
+
+    #[test]
+    fn test_prune_cage() {
+        let cage = Cage { cells: vec![0, 1, 2], sum: 7 };
+        let all = 0b11_1111_1110;
+        let mut masks = vec![all, all, all];
+        cage.prune(&mut masks, 9);
+        assert_eq!(masks, vec![0b1_0110; 3]);
+
+        // 7 is 1 + 2 + 4 only, so with a 4 placed the others hold 1 or 2
+        let mut masks = vec![1 << 4, all, 0b1100];
+        cage.prune(&mut masks, 9);
+        assert_eq!(masks, vec![1 << 4, 1 << 1, 1 << 2]);
+
+        let mut masks = vec![1 << 5, all, all];
+        cage.prune(&mut masks, 9);
+        assert!(masks.contains(&0));
+    }
+
+    #[test]
+    fn test_solve_killer() {
+        let cages = parse_cages(CAGES, Size::STANDARD).unwrap();
+        let layout = Layout::killer(Size::STANDARD, &cages);
+        let empty = ".".repeat(81);
+        assert_eq!(solve_in(&empty, &layout), Some(SOLUTION.to_string()));
+        assert!(has_unique_solution_in(&empty, &layout));
+        assert_eq!(layout.parse(&format!("{}7{}", ".".repeat(76), ".".repeat(4))), None);
+
+        let puzzle = Rc::new(RefCell::new(layout.parse(&empty).unwrap()));
+        let propagation = GridTask::propagate(
+            Rc::clone(&puzzle),
+            Rc::new(RefCell::new(HashMap::new())),
+            Rc::new(layout)
+        );
+        assert!(!matches!(propagation, Propagation::Contradiction));
+        let solution = str_to_vecu8(SOLUTION);
+        let placed: Vec<usize> = (0..81).filter(|&i| puzzle.borrow()[i] != 0).collect();
+        assert!(placed.len() >= 2);
+        assert!(placed.iter().all(|&i| puzzle.borrow()[i] == solution[i]));
+    }
+}
@@ -0,0 +1,191 @@
+use crate::{ adjacent_pairs, constraint::prune_pair, utils::parse_pair_marks, Size };
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DotKind {
+    /// A white dot: the digits are consecutive.
+    White,
+    /// A black dot: one digit is twice the other.
+    Black,
+    /// The digits are not consecutive, as between any two neighbours of non-consecutive
+    /// Sudoku.
+    NonConsecutive,
+    /// No dot where every dot is given: the digits are neither consecutive nor one twice the
+    /// other.
+    Blank,
+}
+
+/// A Kropki dot, or its absence, between two cells side by side.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dot {
+    pub cells: [usize; 2],
+    pub kind: DotKind,
+}
+
+impl Dot {
+    fn fits(&self, a: u8, b: u8) -> bool {
+        let consecutive = a.abs_diff(b) == 1;
+        let double = a == 2 * b || b == 2 * a;
+        match self.kind {
+            DotKind::White => consecutive,
+            DotKind::Black => double,
+            DotKind::NonConsecutive => !consecutive,
+            DotKind::Blank => !consecutive && !double,
+        }
+    }
+
+    /// Keeps the candidates of each cell which fit some candidate of the other.
+    pub fn prune(&self, masks: &mut [u32]) {
+        prune_pair(masks, |a, b| self.fits(a, b));
+    }
+}
+
+/// Reads Kropki dots, one per line of `clue_lines` as `w` for white or `b` for black, a colon
+/// and the two cells it sits between, e.g. `w: r1c1 r1c2`. Returns `None` unless the cells of
+/// every dot are side by side and hold no other dot.
+pub fn parse_dots(text: &str, size: Size) -> Option<Vec<Dot>> {
+    parse_pair_marks(text, size)?
+        .into_iter()
+        .map(|(label, cells)| {
+            let kind = match label.as_str() {
+                "w" | "W" => DotKind::White,
+                "b" | "B" => DotKind::Black,
+                _ => {
+                    return None;
+                }
+            };
+            Some(Dot { cells, kind })
+        })
+        .collect()
+}
+
+/// The blank dots between every two cells side by side which hold none of `dots`, for
+/// puzzles where all the dots are given.
+pub fn negative_dots(dots: &[Dot], size: Size) -> Vec<Dot> {
+    adjacent_pairs(size)
+        .into_iter()
+        .filter(|&cells| dots.iter().all(|dot| dot.cells != cells))
+        .map(|cells| Dot { cells, kind: DotKind::Blank })
+        .collect()
+}
+
+/// The rule of non-consecutive Sudoku, forbidding consecutive digits side by side.
+pub fn non_consecutive_dots(size: Size) -> Vec<Dot> {
+    adjacent_pairs(size)
+        .into_iter()
+        .map(|cells| Dot { cells, kind: DotKind::NonConsecutive })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ has_unique_solution_in, solve_in, Layout };
+
+    use super::*;
+
+    // every dot of a 6x6 grid with boxes 3 wide and 2 tall
+    const DOTS: &str =
+        "b: r1c3 r1c4
+w: r1c3 r2c3
+w: r1c4 r1c5
+b: r1c4 r2c4
+w: r1c6 r2c6
+w: r2c1 r2c2
+w: r2c2 r3c2
+w: r2c3 r2c4
+b: r2c6 r3c6
+w: r3c1 r4c1
+w: r3c2 r3c3
+w: r3c5 r3c6
+w: r3c5 r4c5
+w: r3c6 r4c6
+b: r4c2 r4c3
+w: r4c2 r5c2
+w: r4c3 r5c3
+w: r4c5 r5c5
+b: r4c6 r5c6
+w: r5c2 r6c2
+w: r5c3 r5c4
+b: r5c4 r5c5
+w: r6c1 r6c2
+w: r6c3 r6c4
+w: r6c5 r6c6";
+    const SOLUTION: &str = "614235235416521643463152152364346521";
+
+    const NON_CONSECUTIVE: &str =
+        "......94.....5.6..6.8......3...........2.93..4....1..67....8.....64.....9.162..3.";
+    const NON_CONSECUTIVE_SOLUTION: &str =
+        "527386941194752683638194257362847519815269374479531826753918462286473195941625738";
+
+    #[test]
+    fn test_parse_dots() {
+        let size = Size::rectangular(3, 2);
+        let dots = parse_dots(DOTS, size).unwrap();
+        assert_eq!(dots.len(), 25);
+        assert_eq!(dots[0], Dot { cells: [2, 3], kind: DotKind::Black });
+        assert_eq!(negative_dots(&dots, size).len(), 60 - 25);
+
+        let dots = parse_dots("# a comment\n\nW: r2c1, r1c1\n", size).unwrap();
+        assert_eq!(dots, vec![Dot { cells: [0, 6], kind: DotKind::White }]);
+
+        // cells apart, the same cells twice, an unknown kind and too many cells
+        assert_eq!(parse_dots("w: r1c1 r2c2", size), None);
+        assert_eq!(parse_dots("w: r1c1 r1c2\nb: r1c2 r1c1", size), None);
+        assert_eq!(parse_dots("x: r1c1 r1c2", size), None);
+        assert_eq!(parse_dots("w: r1c1 r1c2 r1c3", size), None);
+    }
+
+    #[test]
+    fn test_prune_dot() {
+        let all = 0b11_1111_1110;
+        let dot = |kind| Dot { cells: [0, 1], kind };
+
+        let mut masks = vec![1 << 5, all];
+        dot(DotKind::White).prune(&mut masks);
+        assert_eq!(masks, vec![1 << 5, (1 << 4) | (1 << 6)]);
+
+        let mut masks = vec![all, 1 << 3];
+        dot(DotKind::Black).prune(&mut masks);
+        assert_eq!(masks, vec![1 << 6, 1 << 3]);
+
+        let mut masks = vec![1 << 1, all];
+        dot(DotKind::Blank).prune(&mut masks);
+        assert_eq!(masks, vec![1 << 1, all & !(1 << 2)]);
+
+        let mut masks = vec![1 << 5, 1 << 6];
+        dot(DotKind::NonConsecutive).prune(&mut masks);
+        assert_eq!(masks, vec![0, 0]);
+    }
+
+    #[test]
+    fn test_solve_kropki() {
+        let size = Size::rectangular(3, 2);
+        let dots = parse_dots(DOTS, size).unwrap();
+        let empty = ".".repeat(36);
+
+        // with every dot given, the dots alone make the puzzle
+        let layout = Layout::kropki(size, &dots, true);
+        assert_eq!(solve_in(&empty, &layout), Some(SOLUTION.to_string()));
+        assert!(has_unique_solution_in(&empty, &layout));
+
+        let layout = Layout::kropki(size, &dots, false);
+        assert!(!has_unique_solution_in(&empty, &layout));
+        let puzzle = format!(".......3{}", ".".repeat(28));
+        assert_eq!(solve_in(&puzzle, &layout), Some(SOLUTION.to_string()));
+        assert!(has_unique_solution_in(&puzzle, &layout));
+        assert!(layout.parse(&format!("12{}", ".".repeat(34))).is_some());
+        let layout = Layout::kropki(size, &dots, true);
+        assert_eq!(layout.parse(&format!("12{}", ".".repeat(34))), None);
+    }
+
+    #[test]
+    fn test_solve_non_consecutive() {
+        let layout = Layout::non_consecutive(Size::STANDARD);
+        assert_eq!(layout.constraints().len(), 144);
+        assert_eq!(
+            solve_in(NON_CONSECUTIVE, &layout),
+            Some(NON_CONSECUTIVE_SOLUTION.to_string())
+        );
+        assert!(has_unique_solution_in(NON_CONSECUTIVE, &layout));
+        assert!(!has_unique_solution_in(NON_CONSECUTIVE, &Layout::standard()));
+    }
+}
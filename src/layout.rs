@@ -0,0 +1,394 @@
+use crate::{
+    constraint::all_digits,
+    arrow::Arrow,
+    killer::Cage,
+    kropki::{ negative_dots, non_consecutive_dots, Dot },
+    sandwich::SandwichClues,
+    thermo::Thermometer,
+    xv::{ negative_xv, Xv },
+    str_to_vecu8_sized,
+    Alphabet,
+    Constraint,
+    House,
+    Piece,
+    Size,
+};
+
+/// The rules of a puzzle as a set of cells plus a list of houses, groups of cells whose
+/// digits must all differ. A house of `size.side()` cells holds every digit once, smaller ones
+/// only forbid repeats. Standard Sudoku is the rows, columns and blocks of a 9×9 board.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layout {
+    size: Size,
+    houses: Vec<(House, Vec<usize>)>,
+    /// Positions in `houses` of the houses each cell belongs to.
+    houses_of: Vec<Vec<usize>>,
+    /// The cells which must differ from each cell, in increasing order.
+    peers: Vec<Vec<usize>>,
+    /// The pieces whose moves forbid equal digits, on top of the houses.
+    pieces: Vec<Piece>,
+    /// The cells a move of some piece away from each cell, in increasing order.
+    move_peers: Vec<Vec<usize>>,
+    constraints: Vec<Constraint>,
+    /// Positions in `constraints` of the constraints each cell takes part in.
+    constraints_of: Vec<Vec<usize>>,
+}
+
+impl Layout {
+    /// The rows, columns and blocks of a board.
+    pub fn new(size: Size) -> Self {
+        let mut layout = Layout::latin_square(size);
+        for b in 0..size.side() {
+            layout.add_house(House::Block(b), size.conjugate_block_index(size.block_head(b)));
+        }
+        layout
+    }
+
+    pub fn standard() -> Self {
+        Layout::new(Size::STANDARD)
+    }
+
+    /// Sudoku-X, where both main diagonals hold every digit as well.
+    pub fn diagonal(size: Size) -> Self {
+        let mut layout = Layout::new(size);
+        for d in 0..2 {
+            let cells = House::Diagonal(d).indexes_sized(size).unwrap_or_default();
+            layout.add_house(House::Diagonal(d), cells);
+        }
+        layout
+    }
+
+    /// Windoku, or Hyper Sudoku, where extra windows hold every digit as well: the drawn ones,
+    /// one cell in from the border, and the phantom ones they imply. Needs square boxes.
+    pub fn windoku(size: Size) -> Self {
+        let mut layout = Layout::new(size);
+        for w in 0..size.side() {
+            let cells = House::Window(w).indexes_sized(size).expect("windows need square boxes");
+            layout.add_house(House::Window(w), cells);
+        }
+        layout
+    }
+
+    /// Jigsaw Sudoku, where irregular regions take the place of the blocks. `regions[i]` is
+    /// the region of cell `i`, as read by `parse_regions`.
+    pub fn jigsaw(size: Size, regions: &[usize]) -> Self {
+        let mut layout = Layout::latin_square(size);
+        for r in 0..size.side() {
+            let cells = (0..size.cells()).filter(|&i| regions[i] == r).collect();
+            layout.add_house(House::Region(r), cells);
+        }
+        layout
+    }
+
+    /// Killer Sudoku, where each cage is a house of its own and adds up to its sum.
+    pub fn killer(size: Size, cages: &[Cage]) -> Self {
+        let mut layout = Layout::new(size);
+        for (c, cage) in cages.iter().enumerate() {
+            layout.add_house(House::Cage(c), cage.cells.clone());
+            layout.add_constraint(Constraint::Cage(cage.clone()));
+        }
+        layout
+    }
+
+    /// Kropki Sudoku, where white dots join consecutive digits and black dots digits one twice
+    /// the other. With `negative`, every dot is given, so that neighbours without a dot are
+    /// neither.
+    pub fn kropki(size: Size, dots: &[Dot], negative: bool) -> Self {
+        let mut layout = Layout::new(size);
+        let blanks = if negative { negative_dots(dots, size) } else { vec![] };
+        for dot in dots.iter().chain(blanks.iter()) {
+            layout.add_constraint(Constraint::Dot(dot.clone()));
+        }
+        layout
+    }
+
+    /// Non-consecutive Sudoku, where no two cells side by side hold consecutive digits.
+    pub fn non_consecutive(size: Size) -> Self {
+        let mut layout = Layout::new(size);
+        for dot in non_consecutive_dots(size) {
+            layout.add_constraint(Constraint::Dot(dot));
+        }
+        layout
+    }
+
+    /// XV Sudoku, where cells joined by an X add up to 10 and by a V to 5. With `negative`,
+    /// every marker is given, so that neighbours without one add up to neither.
+    pub fn xv(size: Size, markers: &[Xv], negative: bool) -> Self {
+        let mut layout = Layout::new(size);
+        let blanks = if negative { negative_xv(markers, size) } else { vec![] };
+        for marker in markers.iter().chain(blanks.iter()) {
+            layout.add_constraint(Constraint::Xv(marker.clone()));
+        }
+        layout
+    }
+
+    /// Thermo Sudoku, where digits strictly increase along each thermometer.
+    pub fn thermo(size: Size, thermometers: &[Thermometer]) -> Self {
+        let mut layout = Layout::new(size);
+        for thermometer in thermometers {
+            layout.add_constraint(Constraint::Thermometer(thermometer.clone()));
+        }
+        layout
+    }
+
+    /// Arrow Sudoku, where the digits along each arrow add up to the number in its circle.
+    pub fn arrows(size: Size, arrows: &[Arrow]) -> Self {
+        let mut layout = Layout::new(size);
+        for arrow in arrows {
+            layout.add_constraint(Constraint::Arrow(arrow.clone()));
+        }
+        layout
+    }
+
+    /// Sandwich Sudoku, where the clues around the grid give the sum of the digits between the
+    /// lowest and highest digit of their row or column.
+    pub fn sandwich(size: Size, clues: &SandwichClues) -> Self {
+        let mut layout = Layout::new(size);
+        for sandwich in clues.sandwiches(size) {
+            layout.add_constraint(Constraint::Sandwich(sandwich));
+        }
+        layout
+    }
+
+    /// The rows and columns of a board without any block, for layouts bringing their own.
+    pub fn latin_square(size: Size) -> Self {
+        let mut layout = Layout {
+            size,
+            houses: vec![],
+            houses_of: vec![vec![]; size.cells()],
+            peers: vec![vec![]; size.cells()],
+            pieces: vec![],
+            move_peers: vec![vec![]; size.cells()],
+            constraints: vec![],
+            constraints_of: vec![vec![]; size.cells()],
+        };
+        for r in 0..size.side() {
+            layout.add_house(House::Row(r), size.conjugate_row_index(r * size.side()));
+        }
+        for c in 0..size.side() {
+            layout.add_house(House::Col(c), size.conjugate_col_index(c));
+        }
+        layout
+    }
+
+    pub fn add_house(&mut self, house: House, cells: Vec<usize>) {
+        assert!(
+            cells.len() <= self.size.side() && cells.iter().all(|&i| i < self.size.cells()),
+            "{} does not fit on the board",
+            house
+        );
+        for &i in cells.iter() {
+            self.houses_of[i].push(self.houses.len());
+            add_peers(&mut self.peers[i], i, &cells);
+        }
+        self.houses.push((house, cells));
+    }
+
+    pub fn add_constraint(&mut self, constraint: Constraint) {
+        assert!(
+            constraint.cells().iter().all(|&i| i < self.size.cells()),
+            "constraint does not fit on the board"
+        );
+        for &i in constraint.cells() {
+            self.constraints_of[i].push(self.constraints.len());
+        }
+        self.constraints.push(constraint);
+    }
+
+    /// Turns on a global anti-knight or anti-king rule: equal digits may no longer be a move
+    /// of the piece apart.
+    pub fn forbid_moves(&mut self, piece: Piece) {
+        if !self.pieces.contains(&piece) {
+            self.pieces.push(piece);
+            self.update_move_peers();
+        }
+    }
+
+    /// Turns a rule of `forbid_moves` off again.
+    pub fn allow_moves(&mut self, piece: Piece) {
+        if self.pieces.contains(&piece) {
+            self.pieces.retain(|&p| p != piece);
+            self.update_move_peers();
+        }
+    }
+
+    /// Rebuilds the peers from the houses and the pieces.
+    fn update_move_peers(&mut self) {
+        for i in 0..self.size.cells() {
+            let mut move_peers = vec![];
+            for piece in self.pieces.iter() {
+                add_peers(&mut move_peers, i, &piece.moves(i, self.size));
+            }
+            let mut peers = vec![];
+            for &h in self.houses_of[i].iter() {
+                add_peers(&mut peers, i, &self.houses[h].1);
+            }
+            add_peers(&mut peers, i, &move_peers);
+            self.peers[i] = peers;
+            self.move_peers[i] = move_peers;
+        }
+    }
+
+    pub fn size(&self) -> Size {
+        self.size
+    }
+
+    pub fn houses(&self) -> &[(House, Vec<usize>)] {
+        &self.houses
+    }
+
+    /// The positions in `houses` of the houses holding a cell.
+    pub fn houses_of(&self, index: usize) -> &[usize] {
+        &self.houses_of[index]
+    }
+
+    /// Whether a house must hold every digit, as opposed to only forbidding repeats.
+    pub fn is_complete(&self, house: usize) -> bool {
+        self.houses[house].1.len() == self.size.side()
+    }
+
+    pub fn peers(&self, index: usize) -> &[usize] {
+        &self.peers[index]
+    }
+
+    pub fn pieces(&self) -> &[Piece] {
+        &self.pieces
+    }
+
+    /// The peers of a cell through the pieces of `forbid_moves`, which may share no house.
+    pub fn move_peers(&self, index: usize) -> &[usize] {
+        &self.move_peers[index]
+    }
+
+    pub fn constraints(&self) -> &[Constraint] {
+        &self.constraints
+    }
+
+    /// The positions in `constraints` of the constraints a cell takes part in.
+    pub fn constraints_of(&self, index: usize) -> &[usize] {
+        &self.constraints_of[index]
+    }
+
+    /// Narrows the candidates of the cells of constraint `c`, given as `mask_of(i)` for each
+    /// cell `i`, and returns them in the order of its cells.
+    pub fn prune_constraint(&self, c: usize, mask_of: impl Fn(usize) -> u32) -> Vec<u32> {
+        let constraint = &self.constraints[c];
+        let mut masks: Vec<u32> = constraint
+            .cells()
+            .iter()
+            .map(|&i| mask_of(i))
+            .collect();
+        constraint.prune(&mut masks, self.size.side());
+        masks
+    }
+
+    pub fn indexes(&self, house: House) -> Option<&[usize]> {
+        self.houses
+            .iter()
+            .find(|(h, _)| *h == house)
+            .map(|(_, cells)| cells.as_slice())
+    }
+
+    /// Reads a puzzle for this layout, or `None` if it has the wrong number of cells or some
+    /// givens break the rules.
+    pub fn parse(&self, puzzle: &str) -> Option<Vec<u8>> {
+        self.check(str_to_vecu8_sized(puzzle, self.size))
+    }
+
+    /// Same as `parse` for a puzzle written with the given alphabet, refusing as well symbols
+    /// foreign to it and digits beyond the side of the board.
+    pub fn parse_with(&self, puzzle: &str, alphabet: &Alphabet) -> Option<Vec<u8>> {
+        let puzzle = alphabet.parse(puzzle)?;
+        if puzzle.iter().any(|&v| (v as usize) > self.size.side()) {
+            return None;
+        }
+        self.check(puzzle)
+    }
+
+    fn check(&self, puzzle: Vec<u8>) -> Option<Vec<u8>> {
+        if
+            puzzle.len() != self.size.cells() ||
+            self.has_conflicts(&puzzle) ||
+            self.breaks_constraints(&puzzle)
+        {
+            return None;
+        }
+        Some(puzzle)
+    }
+
+    /// Whether the filled cells leave some constraint without any way to be met.
+    pub fn breaks_constraints(&self, puzzle: &[u8]) -> bool {
+        let all = all_digits(self.size.side());
+        (0..self.constraints.len()).any(|c| {
+            self.prune_constraint(c, |i| if puzzle[i] == 0 { all } else { 1 << puzzle[i] })
+                .contains(&0)
+        })
+    }
+
+    /// Whether some digit appears twice in a house.
+    pub fn has_conflicts(&self, puzzle: &[u8]) -> bool {
+        (0..puzzle.len())
+            .filter(|&i| puzzle[i] != 0)
+            .any(|i| self.peers[i].iter().any(|&j| puzzle[j] == puzzle[i]))
+    }
+}
+
+/// Makes the digit of cell `i` differ from those of `cells` as well as from `peers`.
+fn add_peers(peers: &mut Vec<usize>, i: usize, cells: &[usize]) {
+    peers.extend(cells.iter().filter(|&&j| j != i));
+    peers.sort_unstable();
+    peers.dedup();
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Layout::standard()
+    }
+}
+
+impl From<Size> for Layout {
+    fn from(size: Size) -> Self {
+        Layout::new(size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::str_to_vecu8;
+
+    use super::*;
+
+    #[test]
+    fn test_layout() {
+        let layout = Layout::standard();
+        assert_eq!(layout.houses().len(), 27);
+        assert_eq!(layout.peers(0).len(), 20);
+        assert_eq!(layout.houses_of(41).len(), 3);
+        assert_eq!(layout.indexes(House::Block(4)), House::Block(4).indexes().as_deref());
+        assert!(layout.is_complete(26));
+
+        let mut layout = Layout::latin_square(Size::new(2));
+        assert_eq!(layout.peers(0), &[1, 2, 3, 4, 8, 12]);
+        layout.add_house(House::Group(0), vec![0, 5, 10, 15]);
+        layout.add_house(House::Group(1), vec![0, 6]);
+        assert_eq!(layout.peers(0), &[1, 2, 3, 4, 5, 6, 8, 10, 12, 15]);
+        assert!(!layout.is_complete(9));
+        assert!(layout.has_conflicts(&str_to_vecu8("1.....1.........")));
+        assert!(!layout.has_conflicts(&str_to_vecu8("1......1........")));
+    }
+
+    #[test]
+    fn test_parse_with() {
+        let layout = Layout::new(Size::new(2));
+        let letters = Alphabet::new("A-D", "_").unwrap();
+        let puzzle = layout.parse("1.3..4.2.1..4..1");
+        assert_eq!(layout.parse_with("A_C__D_B_A__D__A", &letters), puzzle);
+        assert_eq!(layout.parse_with("A.C__D_B_A__D__A", &letters), None);
+        assert_eq!(layout.parse_with("AA______________", &letters), None);
+        assert_eq!(layout.parse_with("A_C__D_B_A__D__", &letters), None);
+
+        // symbols the alphabet knows but the board has no room for
+        let letters = Alphabet::new("A-I", "_").unwrap();
+        assert_eq!(layout.parse_with("E_______________", &letters), None);
+    }
+}
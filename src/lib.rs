@@ -1,10 +1,158 @@
+pub mod size;
+pub use size::Size;
+
+pub mod alphabet;
+pub use alphabet::Alphabet;
+
 pub mod utils;
 pub use utils::str_to_vecu8;
+pub use utils::str_to_vecu8_sized;
 pub use utils::vecu8_to_str;
+pub use utils::vecu8_to_str_sized;
 pub use utils::show;
+pub use utils::show_sized;
+pub use utils::show_with;
+pub use utils::render_with;
+pub use utils::render_marked;
+pub use utils::is_connected;
+pub use utils::cell_name;
+pub use utils::cell_name_sized;
+pub use utils::parse_cell;
+pub use utils::clue_lines;
+pub use utils::adjacent_pairs;
+pub use utils::are_touching;
+pub use utils::has_conflicts;
+pub use utils::has_conflicts_sized;
+pub use utils::block_of;
 pub use utils::conjugate_row_index;
 pub use utils::conjugate_col_index;
 pub use utils::conjugate_block_index;
 
+pub mod house;
+pub use house::House;
+
+pub mod layout;
+pub use layout::Layout;
+
+pub mod constraint;
+pub use constraint::Constraint;
+
+pub mod diagonal;
+pub use diagonal::render_diagonal;
+pub use diagonal::show_diagonal;
+
+pub mod windoku;
+pub use windoku::render_windoku;
+pub use windoku::show_windoku;
+
+pub mod chess;
+pub use chess::Piece;
+
+pub mod killer;
+pub use killer::parse_cages;
+pub use killer::Cage;
+
+pub mod kropki;
+pub use kropki::negative_dots;
+pub use kropki::non_consecutive_dots;
+pub use kropki::parse_dots;
+pub use kropki::Dot;
+pub use kropki::DotKind;
+
+pub mod xv;
+pub use xv::negative_xv;
+pub use xv::parse_xv;
+pub use xv::render_xv;
+pub use xv::show_xv;
+pub use xv::Xv;
+pub use xv::XvKind;
+
+pub mod thermo;
+pub use thermo::parse_thermometers;
+pub use thermo::Thermometer;
+
+pub mod arrow;
+pub use arrow::parse_arrows;
+pub use arrow::Arrow;
+
+pub mod sandwich;
+pub use sandwich::parse_sandwich_clues;
+pub use sandwich::render_sandwich;
+pub use sandwich::show_sandwich;
+pub use sandwich::Sandwich;
+pub use sandwich::SandwichClues;
+
+pub mod jigsaw;
+pub use jigsaw::parse_regions;
+pub use jigsaw::render_jigsaw;
+pub use jigsaw::show_jigsaw;
+
+pub mod solve_step;
+pub use solve_step::render_steps;
+pub use solve_step::render_steps_sized;
+pub use solve_step::SolveStep;
+pub use solve_step::Technique;
+
 pub mod grid_task;
 pub use grid_task::GridTask;
+pub use grid_task::Propagation;
+
+pub mod strategies;
+pub use strategies::find_elimination;
+pub use strategies::find_elimination_in;
+
+pub mod forcing;
+pub use forcing::find_forcing_chain;
+pub use forcing::find_forcing_chain_in;
+pub use forcing::ForcingChain;
+
+pub mod solver;
+pub use solver::solution_path;
+pub use solver::solution_path_in;
+pub use solver::count_solutions;
+pub use solver::has_unique_solution;
+pub use solver::count_solutions_sized;
+pub use solver::has_unique_solution_sized;
+pub use solver::solve;
+pub use solver::count_solutions_in;
+pub use solver::has_unique_solution_in;
+pub use solver::solve_in;
+
+pub mod rating;
+pub use rating::rate;
+pub use rating::rate_in;
+pub use rating::Band;
+pub use rating::Rating;
+
+pub mod hint;
+pub use hint::hint;
+pub use hint::hint_in;
+pub use hint::Hint;
+
+pub mod rng;
+pub use rng::Rng;
+
+pub mod generator;
+pub use generator::generate_solution;
+pub use generator::generate_puzzle;
+pub use generator::try_generate_puzzle;
+pub use generator::GeneratorOptions;
+pub use generator::GeneratedPuzzle;
+pub use generator::ClueLayout;
+pub use generator::Symmetry;
+pub use generator::generate_puzzle_with_difficulty;
+pub use generator::DifficultyTarget;
+pub use generator::Budget;
+
+pub mod minimal;
+pub use minimal::is_minimal;
+pub use minimal::minimize;
+pub use minimal::redundant_givens;
+
+pub mod transform;
+pub use transform::Transform;
+
+pub mod canonical;
+pub use canonical::are_equivalent;
+pub use canonical::canonical_form;
+pub use canonical::canonicalize;
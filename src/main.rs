@@ -1,42 +1,54 @@
+#[cfg(test)]
 use std::{ cell::RefCell, collections::HashMap, rc::Rc };
 
-use sudoku_solver_rust::{ str_to_vecu8, vecu8_to_str, GridTask };
+#[cfg(test)]
+use sudoku_solver_rust::{
+    find_forcing_chain,
+    str_to_vecu8,
+    vecu8_to_str,
+    GridTask,
+    Propagation,
+    Layout,
+};
 
 fn main() {}
 
+/// Lists every solution of a standard 9x9 puzzle, the reference the solver is tested against;
+/// other sizes and layouts go through `count_solutions_in` and `solve_in`.
+#[cfg(test)]
 fn solve_sequentially(puzzle: &str) -> Vec<String> {
     let puzzle_vec = Rc::new(RefCell::new(str_to_vecu8(puzzle)));
     let possible_values_map: Rc<_> = Rc::new(RefCell::new(HashMap::<usize, Rc<Vec<u8>>>::new()));
-    let mut tasks = GridTask::generate_tasks(
+    let propagation = GridTask::propagate(
         Rc::clone(&puzzle_vec),
-        Rc::clone(&possible_values_map)
+        Rc::clone(&possible_values_map),
+        Rc::new(Layout::standard())
     );
-
-    while tasks.iter().any(|t| !t.done()) {
-        tasks.iter_mut().for_each(|t| t.run());
-
-        let tasks_count_backup = tasks.len();
-        tasks = tasks
-            .into_iter()
-            .filter(|t| !t.updated())
-            .collect();
-
-        if tasks.len() == 0 {
-            let res = vecu8_to_str(puzzle_vec.try_borrow().unwrap().as_ref());
-            return vec![res];
+    let tasks = match propagation {
+        Propagation::Solved => {
+            return vec![vecu8_to_str(puzzle_vec.borrow().as_ref())];
         }
-        if tasks.iter().any(|t| t.possible_values().len() == 0) {
+        Propagation::Contradiction => {
             return vec![];
         }
+        Propagation::Stalled(tasks) => tasks,
+    };
 
-        if tasks.len() != tasks_count_backup {
-            tasks.iter_mut().for_each(|t| t.reset_done());
+    // try the forcing chains before guessing, they keep the conclusions true in every solution
+    if let Some(chain) = find_forcing_chain(&puzzle_vec.borrow(), &possible_values_map.borrow()) {
+        if chain.is_contradiction() {
+            return vec![];
         }
+        let mut forced_puzzle = puzzle_vec.borrow().clone();
+        chain
+            .placements()
+            .iter()
+            .for_each(|&(i, v)| {
+                forced_puzzle[i] = v;
+            });
+        return solve_sequentially(&vecu8_to_str(&forced_puzzle));
     }
 
-    if tasks.len() < 1 {
-        return vec![];
-    }
     let mut recursion_puzzle = String::from(puzzle);
     let recursion_base = &tasks[0];
     recursion_base
@@ -135,7 +147,7 @@ mod tests {
                     cases.push(Case {
                         input: String::from(case[0]),
                         expected_output: ExpectedOutput::SolutionsCount(
-                            usize::from_str_radix(case[1], 10).unwrap()
+                            case[1].parse::<usize>().unwrap()
                         ),
                         comment: String::new(),
                     });
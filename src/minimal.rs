@@ -0,0 +1,87 @@
+use crate::{ has_unique_solution, str_to_vecu8, vecu8_to_str };
+
+/// The givens which can go without the puzzle losing its unique solution.
+pub fn redundant_givens(puzzle: &str) -> Vec<usize> {
+    let mut grid = str_to_vecu8(puzzle);
+    (0..grid.len())
+        .filter(|&i| {
+            let given = grid[i];
+            if given == 0 {
+                return false;
+            }
+            grid[i] = 0;
+            let redundant = has_unique_solution(&vecu8_to_str(&grid));
+            grid[i] = given;
+            redundant
+        })
+        .collect()
+}
+
+/// Whether the puzzle has a unique solution which needs every one of its givens.
+pub fn is_minimal(puzzle: &str) -> bool {
+    has_unique_solution(puzzle) && redundant_givens(puzzle).is_empty()
+}
+
+/// Removes givens one by one for as long as the solution stays unique. The cells in `keep`
+/// are tried last, so they survive whenever a minimal puzzle with them exists along the way.
+/// Returns `None` if the puzzle does not have a unique solution to begin with.
+pub fn minimize(puzzle: &str, keep: &[usize]) -> Option<String> {
+    if !has_unique_solution(puzzle) {
+        return None;
+    }
+
+    let mut grid = str_to_vecu8(puzzle);
+    let (kept, others): (Vec<usize>, Vec<usize>) = (0..grid.len())
+        .filter(|&i| grid[i] != 0)
+        .partition(|i| keep.contains(i));
+    for i in others.into_iter().chain(kept) {
+        let given = grid[i];
+        grid[i] = 0;
+        if !has_unique_solution(&vecu8_to_str(&grid)) {
+            grid[i] = given;
+        }
+    }
+
+    Some(vecu8_to_str(&grid))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_minimal() {
+        let minimal =
+            "........2..8.1.9..5....3.4....1.93...6..3..8...37......4......53.1.7.8..2........";
+        let solution =
+            "639847512478512963512693748724189356965234187183765294847921635351476829296358471";
+        let not_unique =
+            "8.........95.......67..........2.485...4.3192......736...651947...732518...894263";
+
+        assert!(!is_minimal(solution));
+        assert!(!is_minimal(not_unique));
+        assert_eq!(redundant_givens(solution).len(), 81);
+        assert!(is_minimal(minimal));
+    }
+
+    #[test]
+    fn test_minimize() {
+        let input =
+            ".5..83.17...1..4..3.4..56.8....3...9.9.8245....6....7...9....5...729..861.36.72.4";
+
+        let minimized = minimize(input, &[]).unwrap();
+        assert!(is_minimal(&minimized));
+        assert!(
+            minimized
+                .chars()
+                .zip(input.chars())
+                .all(|(m, i)| m == '.' || m == i)
+        );
+
+        let kept = minimize(input, &[1, 4, 5]).unwrap();
+        assert!(is_minimal(&kept));
+        assert!([1, 4, 5].iter().all(|&i| kept.as_bytes()[i] != b'.'));
+
+        assert_eq!(minimize(&".".repeat(81), &[]), None);
+    }
+}
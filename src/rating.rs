@@ -0,0 +1,156 @@
+use std::fmt;
+
+use crate::{ solution_path_in, House, Layout, SolveStep, Technique };
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Band {
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+    Extreme,
+}
+
+impl Band {
+    pub fn of(score: f32) -> Band {
+        match score {
+            s if s <= 2.3 => Band::Easy,
+            s if s <= 2.8 => Band::Medium,
+            s if s <= 4.0 => Band::Hard,
+            s if s <= 6.0 => Band::Expert,
+            _ => Band::Extreme,
+        }
+    }
+}
+
+impl fmt::Display for Band {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Band::Easy => "easy",
+            Band::Medium => "medium",
+            Band::Hard => "hard",
+            Band::Expert => "expert",
+            Band::Extreme => "extreme",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rating {
+    /// Difficulty of the hardest step, on a Sudoku Explainer like scale.
+    pub score: f32,
+    pub hardest: Technique,
+    pub steps: usize,
+    /// The techniques the solution path uses, each once, in the order they first come up.
+    pub techniques: Vec<Technique>,
+    pub guessed: bool,
+    pub band: Band,
+}
+
+impl fmt::Display for Rating {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:.1} ({}, {}, {} steps)", self.score, self.band, self.hardest, self.steps)?;
+        if self.guessed {
+            write!(f, ", needs guessing")?;
+        }
+        Ok(())
+    }
+}
+
+/// Difficulty of a single step, following the Sudoku Explainer ratings where they exist.
+pub fn difficulty(step: &SolveStep) -> f32 {
+    match step.technique {
+        Technique::HiddenSingle =>
+            match step.houses[0] {
+                House::Block(_) => 1.2,
+                _ => 1.5,
+            }
+        Technique::NakedSingle => 2.3,
+        Technique::Pointing => 2.6,
+        Technique::Claiming => 2.8,
+        Technique::NakedPair => 3.0,
+        Technique::XWing => 3.2,
+        Technique::HiddenPair => 3.4,
+        Technique::NakedTriple => 3.6,
+        Technique::Swordfish => 3.8,
+        Technique::HiddenTriple => 4.0,
+        Technique::NakedQuad => 5.0,
+        Technique::Jellyfish => 5.2,
+        Technique::HiddenQuad => 5.4,
+        Technique::CellForcingChain => 8.3,
+        Technique::UnitForcingChain => 8.5,
+        Technique::Guess => 10.0,
+    }
+}
+
+pub fn rate_steps(steps: &[SolveStep]) -> Rating {
+    let hardest = steps
+        .iter()
+        .max_by(|a, b| difficulty(a).total_cmp(&difficulty(b)));
+    let (score, hardest) = match hardest {
+        Some(step) => (difficulty(step), step.technique),
+        None => (0.0, Technique::NakedSingle),
+    };
+
+    let mut techniques = vec![];
+    for step in steps {
+        if !techniques.contains(&step.technique) {
+            techniques.push(step.technique);
+        }
+    }
+
+    Rating {
+        score,
+        hardest,
+        steps: steps.len(),
+        techniques,
+        guessed: steps.iter().any(|s| s.technique == Technique::Guess),
+        band: Band::of(score),
+    }
+}
+
+/// Rates the puzzle by the hardest technique its solution path needs, or `None` if it has
+/// no solution.
+pub fn rate(puzzle: &str) -> Option<Rating> {
+    rate_in(puzzle, &Layout::standard())
+}
+
+/// Same as `rate` for a puzzle with the given layout.
+pub fn rate_in(puzzle: &str, layout: &Layout) -> Option<Rating> {
+    solution_path_in(puzzle, layout).map(|steps| rate_steps(&steps))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate() {
+        let easy =
+            ".5..83.17...1..4..3.4..56.8....3...9.9.8245....6....7...9....5...729..861.36.72.4";
+        let rating = rate(easy).unwrap();
+        assert_eq!(rating.band, Band::Easy);
+        assert!(!rating.guessed);
+        assert_eq!(rating.steps, 47);
+        assert_eq!(rating.techniques, vec![Technique::NakedSingle, Technique::HiddenSingle]);
+
+        let forcing =
+            "........8..3...4...9..2..6.....79.......612...6.5.2.7...8...5...1.....2.4.5.....3";
+        let rating = rate(forcing).unwrap();
+        assert_eq!(rating.band, Band::Extreme);
+
+        let no_solution =
+            "1...5.2.9..7.......6.......2...........5.1..2....2.39.3.4.9...15...1...3...8...4.";
+        assert_eq!(rate(no_solution), None);
+    }
+
+    #[test]
+    fn test_band_of() {
+        assert_eq!(Band::of(1.2), Band::Easy);
+        assert_eq!(Band::of(2.6), Band::Medium);
+        assert_eq!(Band::of(3.2), Band::Hard);
+        assert_eq!(Band::of(5.4), Band::Expert);
+        assert_eq!(Band::of(8.3), Band::Extreme);
+    }
+}
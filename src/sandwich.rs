@@ -0,0 +1,233 @@
+use crate::{ killer::prune_sum, render_marked, utils::{ clue_lines, split_items }, Size };
+
+/// A sandwich clue: the digits of a row or column lying between its lowest and highest digit,
+/// 1 and 9 on a 9×9, add up to `sum`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sandwich {
+    pub cells: Vec<usize>,
+    pub sum: u32,
+}
+
+impl Sandwich {
+    /// Tries every placement of the lowest and highest digit, keeping the candidates of the
+    /// placements for which the cells between them can still add up to the sum.
+    pub fn prune(&self, masks: &mut [u32], side: usize) {
+        let (low, high) = (1u32 << 1, 1u32 << side);
+        let mut support = vec![0u32; masks.len()];
+        for a in (0..masks.len()).filter(|&a| masks[a] & low != 0) {
+            for b in (0..masks.len()).filter(|&b| b != a && masks[b] & high != 0) {
+                let between = a.min(b) + 1..a.max(b);
+                let mut placed: Vec<u32> = masks
+                    .iter()
+                    .map(|&mask| mask & !(low | high))
+                    .collect();
+                placed[a] = low;
+                placed[b] = high;
+                if between.is_empty() {
+                    if self.sum != 0 {
+                        continue;
+                    }
+                } else {
+                    prune_sum(&mut placed[between], self.sum, side);
+                }
+                if placed.contains(&0) {
+                    continue;
+                }
+                for (support, mask) in support.iter_mut().zip(placed) {
+                    *support |= mask;
+                }
+            }
+        }
+
+        for (mask, support) in masks.iter_mut().zip(support) {
+            *mask &= support;
+        }
+    }
+}
+
+/// The sandwich clues of a puzzle, `None` where a row or column has none.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SandwichClues {
+    pub rows: Vec<Option<u32>>,
+    pub cols: Vec<Option<u32>>,
+}
+
+impl SandwichClues {
+    /// The constraints of the clues, rows first.
+    pub fn sandwiches(&self, size: Size) -> Vec<Sandwich> {
+        let rows = self.rows
+            .iter()
+            .enumerate()
+            .filter_map(|(r, sum)| sum.map(|sum| (size.conjugate_row_index(r * size.side()), sum)));
+        let cols = self.cols
+            .iter()
+            .enumerate()
+            .filter_map(|(c, sum)| sum.map(|sum| (size.conjugate_col_index(c), sum)));
+        rows.chain(cols)
+            .map(|(cells, sum)| Sandwich { cells, sum })
+            .collect()
+    }
+}
+
+/// Reads the clues around a grid as two lines of `clue_lines`, `c:` followed by the clues above
+/// the columns from left to right and `r:` followed by the clues left of the rows from top to
+/// bottom, with `.` where there is none, e.g. `c: 10 . 35 0 . . . . 8`. Returns `None` unless
+/// each line has a clue per row or column and every sum can be made between the lowest and
+/// highest digit.
+pub fn parse_sandwich_clues(text: &str, size: Size) -> Option<SandwichClues> {
+    let side = size.side() as u32;
+    let largest = (side * (side - 1)) / 2 - 1;
+    let (mut rows, mut cols) = (None, None);
+    for line in clue_lines(text) {
+        let (label, clues) = line.split_once(':')?;
+        let clues = split_items(clues)
+            .map(|clue| {
+                match clue {
+                    "." => Some(None),
+                    _ => clue.parse::<u32>().ok().filter(|&sum| sum <= largest).map(Some),
+                }
+            })
+            .collect::<Option<Vec<Option<u32>>>>()?;
+        if clues.len() != size.side() {
+            return None;
+        }
+        let slot = match label.trim() {
+            "r" | "R" => &mut rows,
+            "c" | "C" => &mut cols,
+            _ => {
+                return None;
+            }
+        };
+        if slot.replace(clues).is_some() {
+            return None;
+        }
+    }
+    Some(SandwichClues { rows: rows?, cols: cols? })
+}
+
+/// The boxed grid of `show` with the column clues above it, written downwards when they take
+/// several digits, and the row clues left of it.
+pub fn render_sandwich(sudoku: &str, size: Size, clues: &SandwichClues) -> String {
+    let label = |clue: &Option<u32>| clue.map(|sum| sum.to_string()).unwrap_or_default();
+    let width = clues.rows.iter().map(|clue| label(clue).len()).max().unwrap_or(0);
+    let height = clues.cols.iter().map(|clue| label(clue).len()).max().unwrap_or(0);
+
+    let mut output = String::new();
+    for k in 0..height {
+        let mut line: Vec<char> = vec![' '; width + 1];
+        for (c, clue) in clues.cols.iter().enumerate() {
+            let position = width + 1 + 2 + 2 * c + c / size.box_width();
+            line.resize(position + 1, ' ');
+            let label: Vec<char> = label(clue).chars().collect();
+            if k + label.len() >= height {
+                line[position] = label[k + label.len() - height];
+            }
+        }
+        let line: String = line.into_iter().collect();
+        output.push_str(line.trim_end());
+        output.push('\n');
+    }
+
+    let mut row = 0;
+    for line in render_marked(sudoku, size, |_| ' ').lines() {
+        let margin = if line.starts_with('|') {
+            row += 1;
+            label(&clues.rows[row - 1])
+        } else {
+            String::new()
+        };
+        output.push_str(&format!("{:>width$} {}\n", margin, line, width = width));
+    }
+    output
+}
+
+pub fn show_sandwich(sudoku: &str, size: Size, clues: &SandwichClues) {
+    print!("{}", render_sandwich(sudoku, size, clues));
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ has_unique_solution_in, solve_in, Layout };
+
+    use super::*;
+
+    const CLUES: &str = "c: 29 2 6 0 4 8 10 35 5\nr: 0 15 4 4 0 0 27 0 31";
+    const PUZZLE: &str =
+        "........7.....................7.........2.5....................5...9.........7.9.";
+    const SOLUTION: &str =
+        "652483917978162435314975628825736149791824563436519872269348751547291386183657294";
+
+    #[test]
+    fn test_parse_sandwich_clues() {
+        let clues = parse_sandwich_clues(CLUES, Size::STANDARD).unwrap();
+        assert_eq!(clues.cols[0], Some(29));
+        assert_eq!(clues.rows[8], Some(31));
+        assert_eq!(clues.sandwiches(Size::STANDARD).len(), 18);
+
+        let none = "c: . . . . . . . . .";
+        let text = format!("# a comment\nr: . . . . 5 . . . .\n\n{}", none);
+        let clues = parse_sandwich_clues(&text, Size::STANDARD).unwrap();
+        let sandwiches = vec![Sandwich { cells: (36..45).collect(), sum: 5 }];
+        assert_eq!(clues.sandwiches(Size::STANDARD), sandwiches);
+
+        // a clue too many, a sum too large, a line twice, a line missing and an unknown line
+        let parse = |text: &str| parse_sandwich_clues(text, Size::STANDARD);
+        assert_eq!(parse("c: 1 2 3 4 5 6 7 8 9 10\nr: . . . . . . . . ."), None);
+        assert_eq!(parse("c: 36 . . . . . . . .\nr: . . . . . . . . ."), None);
+        assert_eq!(parse(&format!("{}\n{}", none, none)), None);
+        assert_eq!(parse(none), None);
+        assert_eq!(parse("x: . . . . . . . . ."), None);
+    }
+
+    #[test]
+    fn test_prune_sandwich() {
+        let all = 0b11_1111_1110;
+        let ends = (1 << 1) | (1 << 9);
+
+        // 35 takes every digit from 2 to 8, so 1 and 9 sit at both ends
+        let sandwich = Sandwich { cells: (0..9).collect(), sum: 35 };
+        let mut masks = vec![all; 9];
+        sandwich.prune(&mut masks, 9);
+        assert_eq!(masks[0], ends);
+        assert_eq!(masks[8], ends);
+        assert!(masks[1..8].iter().all(|&mask| mask == all & !ends));
+
+        // with 1 and 9 at r1c1 and r1c3, the 5 lies between them
+        let sandwich = Sandwich { cells: (0..9).collect(), sum: 5 };
+        let mut masks = vec![all; 9];
+        masks[0] = 1 << 1;
+        masks[2] = 1 << 9;
+        sandwich.prune(&mut masks, 9);
+        assert_eq!(masks[1], 1 << 5);
+
+        masks[1] = 1 << 4;
+        sandwich.prune(&mut masks, 9);
+        assert!(masks.contains(&0));
+    }
+
+    #[test]
+    fn test_solve_sandwich() {
+        let clues = parse_sandwich_clues(CLUES, Size::STANDARD).unwrap();
+        let layout = Layout::sandwich(Size::STANDARD, &clues);
+        assert_eq!(solve_in(PUZZLE, &layout), Some(SOLUTION.to_string()));
+        assert!(has_unique_solution_in(PUZZLE, &layout));
+        assert!(!has_unique_solution_in(PUZZLE, &Layout::standard()));
+
+        // row 1 adds up to nothing, so 1 and 9 are neighbours there
+        assert_eq!(layout.parse(&format!("1.9{}", ".".repeat(78))), None);
+    }
+
+    #[test]
+    fn test_render_sandwich() {
+        let clues = parse_sandwich_clues(CLUES, Size::STANDARD).unwrap();
+        let rendered = render_sandwich(SOLUTION, Size::STANDARD, &clues);
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines[0], "     2             1 3");
+        assert_eq!(lines[1], "     9 2 6  0 4 8  0 5 5");
+        assert_eq!(lines[2], "   +------+------+------+");
+        assert_eq!(lines[3], " 0 | 6 5 2| 4 8 3| 9 1 7|");
+        assert_eq!(lines[4], "15 | 9 7 8| 1 6 2| 4 3 5|");
+        assert_eq!(lines.len(), 15);
+        show_sandwich(PUZZLE, Size::STANDARD, &clues);
+    }
+}
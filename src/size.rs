@@ -0,0 +1,149 @@
+use std::fmt;
+
+/// The dimensions of a board made of boxes `box_width` cells wide and `box_height` cells tall,
+/// so that every row, column and box holds the digits 1 to `box_width * box_height` once.
+/// Standard Sudoku has square boxes of 3.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Size {
+    box_width: usize,
+    box_height: usize,
+}
+
+impl Size {
+    pub const STANDARD: Size = Size { box_width: 3, box_height: 3 };
+
+    /// The most digits a board can have.
+    pub const MAX_SIDE: usize = 25;
+
+    /// A board of square boxes, e.g. 2 for 4×4 or 4 for 16×16.
+    pub fn new(box_size: usize) -> Self {
+        Size::rectangular(box_size, box_size)
+    }
+
+    /// A board of rectangular boxes, e.g. 3 wide and 2 tall for 6×6. Digits are stored as `u8`
+    /// and parsed as base 36 symbols, hence the upper bound of 25 digits.
+    pub fn rectangular(box_width: usize, box_height: usize) -> Self {
+        assert!(
+            box_width >= 2 && box_height >= 2 && box_width * box_height <= Size::MAX_SIDE,
+            "boxes must be at least 2 by 2 and hold at most 25 cells"
+        );
+        Size { box_width, box_height }
+    }
+
+    /// The size of a board of square boxes with `cells` cells, if there is one.
+    pub fn of_cells(cells: usize) -> Option<Size> {
+        (2..=5).map(Size::new).find(|size| size.cells() == cells)
+    }
+
+    pub fn box_width(&self) -> usize {
+        self.box_width
+    }
+
+    pub fn box_height(&self) -> usize {
+        self.box_height
+    }
+
+    /// The number of digits, which is also the length of a row, column or box.
+    pub fn side(&self) -> usize {
+        self.box_width * self.box_height
+    }
+
+    pub fn cells(&self) -> usize {
+        self.side() * self.side()
+    }
+
+    pub fn row_of(&self, i: usize) -> usize {
+        i / self.side()
+    }
+
+    pub fn col_of(&self, i: usize) -> usize {
+        i % self.side()
+    }
+
+    /// The number of boxes side by side in a band, which is `box_height` as the side is shared.
+    fn boxes_per_band(&self) -> usize {
+        self.side() / self.box_width
+    }
+
+    /// The number of the block holding a cell, counting blocks row by row.
+    pub fn block_of(&self, i: usize) -> usize {
+        (self.row_of(i) / self.box_height) * self.boxes_per_band() +
+            self.col_of(i) / self.box_width
+    }
+
+    /// The top left cell of block `b`.
+    pub fn block_head(&self, b: usize) -> usize {
+        let band = b / self.boxes_per_band();
+        let stack = b % self.boxes_per_band();
+        band * self.box_height * self.side() + stack * self.box_width
+    }
+
+    pub fn conjugate_row_index(&self, i: usize) -> Vec<usize> {
+        let row_head = self.row_of(i) * self.side();
+        (row_head..row_head + self.side()).collect()
+    }
+
+    pub fn conjugate_col_index(&self, i: usize) -> Vec<usize> {
+        (self.col_of(i)..self.cells()).step_by(self.side()).collect()
+    }
+
+    pub fn conjugate_block_index(&self, i: usize) -> Vec<usize> {
+        let head = self.block_head(self.block_of(i));
+        (0..self.side())
+            .map(|k| head + (k / self.box_width) * self.side() + k % self.box_width)
+            .collect()
+    }
+}
+
+impl Default for Size {
+    fn default() -> Self {
+        Size::STANDARD
+    }
+}
+
+impl fmt::Display for Size {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}x{}", self.side(), self.side())?;
+        if self.box_width != self.box_height {
+            write!(f, " ({}x{} boxes)", self.box_width, self.box_height)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_size() {
+        let size = Size::new(2);
+        assert_eq!((size.side(), size.cells()), (4, 16));
+        assert_eq!(size.conjugate_row_index(6), vec![4, 5, 6, 7]);
+        assert_eq!(size.conjugate_col_index(6), vec![2, 6, 10, 14]);
+        assert_eq!(size.conjugate_block_index(6), vec![2, 3, 6, 7]);
+        assert_eq!(Size::of_cells(256), Some(Size::new(4)));
+        assert_eq!(Size::of_cells(80), None);
+
+        let size = Size::new(4);
+        assert_eq!(size.block_of(255), 15);
+        assert_eq!(size.conjugate_block_index(255)[0], 204);
+        assert_eq!(Size::STANDARD.to_string(), "9x9");
+    }
+
+    #[test]
+    fn test_rectangular_size() {
+        let size = Size::rectangular(3, 2);
+        assert_eq!(size.side(), 6);
+        assert_eq!(size.conjugate_block_index(0), vec![0, 1, 2, 6, 7, 8]);
+        assert_eq!(size.conjugate_block_index(35), vec![27, 28, 29, 33, 34, 35]);
+        assert_eq!(size.block_of(9), 1);
+        assert_eq!(size.block_of(12), 2);
+        assert_eq!(size.to_string(), "6x6 (3x2 boxes)");
+
+        let size = Size::rectangular(4, 3);
+        assert_eq!(size.block_of(143), 11);
+        assert_eq!(size.block_head(11), 108 + 8);
+        assert_eq!(size.conjugate_block_index(0), vec![0, 1, 2, 3, 12, 13, 14, 15, 24, 25, 26, 27]);
+    }
+}
@@ -0,0 +1,290 @@
+use std::fmt;
+
+use crate::{ cell_name_sized, forcing::ForcingKind, ForcingChain, House, Layout, Size };
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Technique {
+    NakedSingle,
+    HiddenSingle,
+    Pointing,
+    Claiming,
+    NakedPair,
+    XWing,
+    HiddenPair,
+    NakedTriple,
+    Swordfish,
+    HiddenTriple,
+    NakedQuad,
+    Jellyfish,
+    HiddenQuad,
+    CellForcingChain,
+    UnitForcingChain,
+    Guess,
+}
+
+impl Technique {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Technique::NakedSingle => "naked single",
+            Technique::HiddenSingle => "hidden single",
+            Technique::Pointing => "pointing",
+            Technique::Claiming => "claiming",
+            Technique::NakedPair => "naked pair",
+            Technique::XWing => "x-wing",
+            Technique::HiddenPair => "hidden pair",
+            Technique::NakedTriple => "naked triple",
+            Technique::Swordfish => "swordfish",
+            Technique::HiddenTriple => "hidden triple",
+            Technique::NakedQuad => "naked quad",
+            Technique::Jellyfish => "jellyfish",
+            Technique::HiddenQuad => "hidden quad",
+            Technique::CellForcingChain => "cell forcing chain",
+            Technique::UnitForcingChain => "unit forcing chain",
+            Technique::Guess => "guess",
+        }
+    }
+}
+
+impl fmt::Display for Technique {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// One deduction of a solution path: which technique was applied to which cells,
+/// digits and houses, and what it placed or eliminated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolveStep {
+    pub technique: Technique,
+    pub cells: Vec<usize>,
+    pub digits: Vec<u8>,
+    pub houses: Vec<House>,
+    pub placements: Vec<(usize, u8)>,
+    pub eliminations: Vec<(usize, u8)>,
+}
+
+impl SolveStep {
+    /// A naked single, left by every house of the layout holding the cell.
+    pub fn naked_single(index: usize, value: u8, layout: &Layout) -> Self {
+        SolveStep {
+            technique: Technique::NakedSingle,
+            cells: vec![index],
+            digits: vec![value],
+            houses: layout
+                .houses_of(index)
+                .iter()
+                .map(|&h| layout.houses()[h].0)
+                .collect(),
+            placements: vec![(index, value)],
+            eliminations: vec![],
+        }
+    }
+
+    pub fn hidden_single(index: usize, value: u8, house: House) -> Self {
+        SolveStep {
+            technique: Technique::HiddenSingle,
+            cells: vec![index],
+            digits: vec![value],
+            houses: vec![house],
+            placements: vec![(index, value)],
+            eliminations: vec![],
+        }
+    }
+
+    pub fn guess(index: usize, value: u8) -> Self {
+        SolveStep {
+            technique: Technique::Guess,
+            cells: vec![index],
+            digits: vec![value],
+            houses: vec![],
+            placements: vec![(index, value)],
+            eliminations: vec![],
+        }
+    }
+}
+
+impl From<&ForcingChain> for SolveStep {
+    fn from(chain: &ForcingChain) -> Self {
+        let (technique, houses) = match chain.kind() {
+            ForcingKind::Cell(_) => (Technique::CellForcingChain, vec![]),
+            ForcingKind::Unit(house, _) => (Technique::UnitForcingChain, vec![house]),
+        };
+        let mut digits: Vec<u8> = chain
+            .branches()
+            .iter()
+            .map(|b| b.value)
+            .collect();
+        digits.dedup();
+
+        SolveStep {
+            technique,
+            cells: chain
+                .branches()
+                .iter()
+                .map(|b| b.index)
+                .fold(vec![], |mut cells, i| {
+                    if !cells.contains(&i) {
+                        cells.push(i);
+                    }
+                    cells
+                }),
+            digits,
+            houses,
+            placements: chain.placements().to_vec(),
+            eliminations: vec![],
+        }
+    }
+}
+
+fn join<T>(items: &[T], f: impl Fn(&T) -> String) -> String {
+    items.iter().map(f).collect::<Vec<String>>().join(", ")
+}
+
+impl SolveStep {
+    /// The step as text, naming the cells as on a board of the given size.
+    pub fn describe(&self, size: Size) -> String {
+        let mut text = String::new();
+        self.write(&mut text, size).expect("writing to a string cannot fail");
+        text
+    }
+
+    fn write(&self, f: &mut impl fmt::Write, size: Size) -> fmt::Result {
+        let cell_name = |i: usize| cell_name_sized(i, size);
+        let cells = join(&self.cells, |&i| cell_name(i));
+        let digits = join(&self.digits, |v| v.to_string());
+        let houses = join(&self.houses, |h| h.to_string());
+        let mut conclusions: Vec<String> = self.placements
+            .iter()
+            .map(|&(i, v)| format!("{}={}", cell_name(i), v))
+            .collect();
+        conclusions.extend(
+            self.eliminations.iter().map(|&(i, v)| format!("{}<>{}", cell_name(i), v))
+        );
+        let conclusions = conclusions.join(", ");
+
+        match self.technique {
+            Technique::NakedSingle =>
+                write!(
+                    f,
+                    "{}: {} is the only candidate left by {}",
+                    self.technique,
+                    conclusions,
+                    houses
+                ),
+            Technique::HiddenSingle =>
+                write!(
+                    f,
+                    "{}: {} is the only place for {} in {}",
+                    self.technique,
+                    conclusions,
+                    digits,
+                    houses
+                ),
+            Technique::Pointing | Technique::Claiming =>
+                write!(
+                    f,
+                    "{}: {} in {} is confined to {}, so {}",
+                    self.technique,
+                    digits,
+                    self.houses[0],
+                    self.houses[1],
+                    conclusions
+                ),
+            Technique::NakedPair | Technique::NakedTriple | Technique::NakedQuad =>
+                write!(
+                    f,
+                    "{}: {} hold only {} in {}, so {}",
+                    self.technique,
+                    cells,
+                    digits,
+                    houses,
+                    conclusions
+                ),
+            Technique::HiddenPair | Technique::HiddenTriple | Technique::HiddenQuad =>
+                write!(
+                    f,
+                    "{}: {} only fit {} in {}, so {}",
+                    self.technique,
+                    digits,
+                    cells,
+                    houses,
+                    conclusions
+                ),
+            Technique::XWing | Technique::Swordfish | Technique::Jellyfish => {
+                let (bases, covers) = self.houses.split_at(self.houses.len() / 2);
+                write!(
+                    f,
+                    "{}: {} in {} is confined to {}, so {}",
+                    self.technique,
+                    digits,
+                    join(bases, |h| h.to_string()),
+                    join(covers, |h| h.to_string()),
+                    conclusions
+                )
+            }
+            Technique::CellForcingChain =>
+                write!(
+                    f,
+                    "{}: each of {} in {} leads to {}",
+                    self.technique,
+                    digits,
+                    cells,
+                    conclusions
+                ),
+            Technique::UnitForcingChain =>
+                write!(
+                    f,
+                    "{}: each place for {} in {} ({}) leads to {}",
+                    self.technique,
+                    digits,
+                    houses,
+                    cells,
+                    conclusions
+                ),
+            Technique::Guess => write!(f, "{}: {}", self.technique, conclusions),
+        }
+    }
+}
+
+impl fmt::Display for SolveStep {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write(f, Size::STANDARD)
+    }
+}
+
+/// Renders a solution path as numbered lines, one per step.
+pub fn render_steps(steps: &[SolveStep]) -> String {
+    render_steps_sized(steps, Size::STANDARD)
+}
+
+pub fn render_steps_sized(steps: &[SolveStep], size: Size) -> String {
+    steps
+        .iter()
+        .enumerate()
+        .map(|(n, step)| format!("{}. {}\n", n + 1, step.describe(size)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_steps() {
+        let steps = vec![
+            SolveStep::naked_single(1, 5, &Layout::standard()),
+            SolveStep::hidden_single(80, 4, House::Block(8)),
+            SolveStep::guess(40, 7)
+        ];
+        let expected_output =
+            "1. naked single: r1c2=5 is the only candidate left by row 1, column 2, block 1
+2. hidden single: r9c9=4 is the only place for 4 in block 9
+3. guess: r5c5=7
+";
+
+        assert_eq!(render_steps(&steps), expected_output);
+
+        let rendered = render_steps_sized(&steps[2..], Size::new(4));
+        assert_eq!(rendered, "1. guess: r3c9=7\n");
+    }
+}
@@ -0,0 +1,416 @@
+use std::{ cell::RefCell, collections::HashMap, rc::Rc };
+
+use crate::{
+    constraint::all_digits,
+    forcing::find_forcing_chain_within,
+    strategies::{ apply_eliminations, find_elimination_in },
+    str_to_vecu8_sized,
+    vecu8_to_str_sized,
+    GridTask,
+    Layout,
+    Propagation,
+    Size,
+    SolveStep,
+};
+
+/// Solves the puzzle the way a person would, recording every step: singles first, then the
+/// easiest elimination technique which helps, forcing chains when they all run dry and a guess
+/// only when nothing else helps, or when the forcing chains have searched too long in vain.
+/// Returns `None` if the puzzle has no solution.
+pub fn solution_path(puzzle: &str) -> Option<Vec<SolveStep>> {
+    solution_path_in(puzzle, &Layout::standard())
+}
+
+/// The branches the forcing chains of a solution path may try in vain, after which every
+/// stall is guessed right away.
+const FORCING_BUDGET: usize = 2048;
+
+/// Same as `solution_path` for a puzzle with the given layout, whose houses and constraints
+/// the singles, the elimination techniques and the forcing chains all follow. Returns `None`
+/// as well if the givens break the rules of the layout.
+pub fn solution_path_in(puzzle: &str, layout: &Layout) -> Option<Vec<SolveStep>> {
+    let mut budget = FORCING_BUDGET;
+    follow_path(layout.parse(puzzle)?, &Rc::new(layout.clone()), &mut budget)
+}
+
+fn follow_path(
+    puzzle: Vec<u8>,
+    layout: &Rc<Layout>,
+    budget: &mut usize
+) -> Option<Vec<SolveStep>> {
+    let puzzle_vec = Rc::new(RefCell::new(puzzle));
+    let possible_values_map: Rc<_> = Rc::new(RefCell::new(HashMap::<usize, Rc<Vec<u8>>>::new()));
+    let mut steps = vec![];
+
+    loop {
+        let tasks = match
+            GridTask::propagate_logged(
+                Rc::clone(&puzzle_vec),
+                Rc::clone(&possible_values_map),
+                Rc::clone(layout),
+                &mut steps
+            )
+        {
+            Propagation::Solved => {
+                return Some(steps);
+            }
+            Propagation::Contradiction => {
+                return None;
+            }
+            Propagation::Stalled(tasks) => tasks,
+        };
+
+        let elimination = find_elimination_in(
+            &puzzle_vec.borrow(),
+            &possible_values_map.borrow(),
+            layout
+        );
+        if let Some(step) = elimination {
+            apply_eliminations(&mut possible_values_map.borrow_mut(), &step.eliminations);
+            steps.push(step);
+            continue;
+        }
+
+        let chain = find_forcing_chain_within(
+            &puzzle_vec.borrow(),
+            &possible_values_map.borrow(),
+            layout,
+            budget
+        );
+        if let Some(chain) = chain {
+            if chain.is_contradiction() {
+                return None;
+            }
+            steps.push(SolveStep::from(&chain));
+            let mut puzzle_vec = puzzle_vec.borrow_mut();
+            chain
+                .placements()
+                .iter()
+                .for_each(|&(i, v)| {
+                    puzzle_vec[i] = v;
+                });
+            continue;
+        }
+
+        let guess_base = &tasks[0];
+        for &v in guess_base.possible_values().iter() {
+            let mut guessed_puzzle = puzzle_vec.borrow().clone();
+            guessed_puzzle[guess_base.index()] = v;
+            if let Some(guessed_steps) = follow_path(guessed_puzzle, layout, budget) {
+                steps.push(SolveStep::guess(guess_base.index(), v));
+                steps.extend(guessed_steps);
+                return Some(steps);
+            }
+        }
+        return None;
+    }
+}
+
+/// The state of the backtracking search: digits used per house, one bit per digit, and the
+/// solutions found so far. Constraints are checked against the filled cells as they come.
+struct Search<'a> {
+    layout: &'a Layout,
+    used: Vec<u32>,
+    count: usize,
+    limit: usize,
+    first: Option<Vec<u8>>,
+}
+
+impl<'a> Search<'a> {
+    /// Marks the givens as used, or returns `None` if two of them clash or the grid does not
+    /// have the cells of the layout.
+    fn new(grid: &[u8], layout: &'a Layout, limit: usize) -> Option<Self> {
+        if grid.len() != layout.size().cells() {
+            return None;
+        }
+        let mut search = Search {
+            layout,
+            used: vec![0; layout.houses().len()],
+            count: 0,
+            limit,
+            first: None,
+        };
+        let mut filled = vec![0; grid.len()];
+        for i in (0..grid.len()).filter(|&i| grid[i] != 0) {
+            if search.free(i, &filled) & (1 << grid[i]) == 0 {
+                return None;
+            }
+            filled[i] = grid[i];
+            search.toggle(i, grid[i]);
+        }
+        Some(search)
+    }
+
+    /// The digits no house of the cell holds yet.
+    fn unused(&self, i: usize) -> u32 {
+        let all = all_digits(self.layout.size().side());
+        self.layout
+            .houses_of(i)
+            .iter()
+            .fold(all, |free, &h| free & !self.used[h])
+    }
+
+    /// The digits cell `i` may take given the filled cells: those left by its houses and by
+    /// its peers through pieces, narrowed by its constraints.
+    fn free(&self, i: usize, grid: &[u8]) -> u32 {
+        let unused = self.layout
+            .move_peers(i)
+            .iter()
+            .fold(self.unused(i), |free, &j| free & !(1 << grid[j]));
+        self.layout
+            .constraints_of(i)
+            .iter()
+            .fold(unused, |free, &c| {
+                let masks = self.layout.prune_constraint(c, |j| {
+                    match grid[j] {
+                        _ if j == i => free,
+                        0 => self.unused(j),
+                        v => 1 << v,
+                    }
+                });
+                let k = self.layout.constraints()[c]
+                    .cells()
+                    .iter()
+                    .position(|&j| j == i)
+                    .unwrap();
+                free & masks[k]
+            })
+    }
+
+    fn toggle(&mut self, i: usize, v: u8) {
+        for &h in self.layout.houses_of(i) {
+            self.used[h] ^= 1 << v;
+        }
+    }
+
+    fn run(&mut self, grid: &mut [u8]) {
+        // branch on the empty cell with the fewest candidates
+        let mut best: Option<(usize, u32)> = None;
+        for i in (0..grid.len()).filter(|&i| grid[i] == 0) {
+            let free = self.free(i, grid);
+            if best.is_none_or(|(_, b)| free.count_ones() < b.count_ones()) {
+                best = Some((i, free));
+                if free.count_ones() <= 1 {
+                    break;
+                }
+            }
+        }
+
+        let (i, free) = match best {
+            Some(best) => best,
+            None => {
+                self.count += 1;
+                if self.first.is_none() {
+                    self.first = Some(grid.to_vec());
+                }
+                return;
+            }
+        };
+        for v in (1..=self.layout.size().side() as u8).filter(|v| free & (1 << v) != 0) {
+            grid[i] = v;
+            self.toggle(i, v);
+            self.run(grid);
+            self.toggle(i, v);
+            grid[i] = 0;
+            if self.count >= self.limit {
+                return;
+            }
+        }
+    }
+}
+
+/// Counts the solutions of the puzzle by a plain backtracking search, stopping at `limit`.
+/// Much faster than listing the solutions, for when only the number matters.
+pub fn count_solutions(puzzle: &str, limit: usize) -> usize {
+    count_solutions_in(puzzle, &Layout::standard(), limit)
+}
+
+pub fn count_solutions_sized(puzzle: &str, size: Size, limit: usize) -> usize {
+    count_solutions_in(puzzle, &Layout::new(size), limit)
+}
+
+pub fn count_solutions_in(puzzle: &str, layout: &Layout, limit: usize) -> usize {
+    let mut grid = str_to_vecu8_sized(puzzle, layout.size());
+    match Search::new(&grid, layout, limit) {
+        Some(mut search) if limit > 0 => {
+            search.run(&mut grid);
+            search.count
+        }
+        _ => 0,
+    }
+}
+
+pub fn has_unique_solution(puzzle: &str) -> bool {
+    has_unique_solution_in(puzzle, &Layout::standard())
+}
+
+pub fn has_unique_solution_sized(puzzle: &str, size: Size) -> bool {
+    has_unique_solution_in(puzzle, &Layout::new(size))
+}
+
+pub fn has_unique_solution_in(puzzle: &str, layout: &Layout) -> bool {
+    count_solutions_in(puzzle, layout, 2) == 1
+}
+
+/// Fills in a puzzle of any size, returning the first solution found.
+pub fn solve(puzzle: &str, size: Size) -> Option<String> {
+    solve_in(puzzle, &Layout::new(size))
+}
+
+/// Fills in a puzzle following the houses of any layout.
+pub fn solve_in(puzzle: &str, layout: &Layout) -> Option<String> {
+    let mut grid = str_to_vecu8_sized(puzzle, layout.size());
+    let mut search = Search::new(&grid, layout, 1)?;
+    search.run(&mut grid);
+    search.first.map(|solution| vecu8_to_str_sized(&solution, layout.size()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        has_conflicts_sized,
+        render_steps,
+        render_steps_sized,
+        str_to_vecu8,
+        vecu8_to_str,
+        House,
+        Technique,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_solution_path() {
+        let input =
+            "........8..3...4...9..2..6.....79.......612...6.5.2.7...8...5...1.....2.4.5.....3";
+        let expected_output =
+            "621943758783615492594728361142879635357461289869532174238197546916354827475286913";
+
+        let steps = solution_path(input).unwrap();
+        let mut puzzle = str_to_vecu8(input);
+        steps
+            .iter()
+            .flat_map(|s| s.placements.iter())
+            .for_each(|&(i, v)| {
+                assert_eq!(puzzle[i], 0);
+                puzzle[i] = v;
+            });
+
+        assert_eq!(vecu8_to_str(&puzzle), expected_output);
+        assert!(steps.iter().any(|s| s.technique == Technique::CellForcingChain));
+        assert!(render_steps(&steps).starts_with("1. "));
+    }
+
+    #[test]
+    fn test_solution_path_without_solution() {
+        let input =
+            "1...5.2.9..7.......6.......2...........5.1..2....2.39.3.4.9...15...1...3...8...4.";
+
+        assert_eq!(solution_path(input), None);
+    }
+
+    #[test]
+    fn test_solution_path_sized() {
+        let cases = [
+            (Size::new(2), "1.3..4.2.1..4..1", "1234341221434321"),
+            (
+                Size::rectangular(3, 2),
+                "1.3....56......5.4.6...1.1.6.....3.2",
+                "123456456123231564564231312645645312",
+            ),
+        ];
+        for (size, input, expected_output) in cases {
+            let steps = solution_path_in(input, &Layout::new(size)).unwrap();
+            let mut puzzle = str_to_vecu8_sized(input, size);
+            steps
+                .iter()
+                .flat_map(|s| s.placements.iter())
+                .for_each(|&(i, v)| {
+                    assert_eq!(puzzle[i], 0);
+                    puzzle[i] = v;
+                });
+            assert_eq!(vecu8_to_str_sized(&puzzle, size), expected_output);
+            assert!(steps.iter().all(|s| s.technique != Technique::Guess));
+        }
+
+        let steps = solution_path_in("1.3..4.2.1..4..1", &Layout::new(Size::new(2))).unwrap();
+        let (i, v) = steps[0].placements[0];
+        let name = format!("r{}c{}={}", i / 4 + 1, (i % 4) + 1, v);
+        assert!(render_steps_sized(&steps, Size::new(2)).lines().next().unwrap().contains(&name));
+    }
+
+    #[test]
+    fn test_count_solutions() {
+        let unique =
+            "........2..8.1.9..5....3.4....1.93...6..3..8...37......4......53.1.7.8..2........";
+        let none =
+            "1...5.2.9..7.......6.......2...........5.1..2....2.39.3.4.9...15...1...3...8...4.";
+        let many =
+            "8.........95.......67..........2.485...4.3192......736...651947...732518...894263";
+
+        assert_eq!(count_solutions(unique, usize::MAX), 1);
+        assert!(has_unique_solution(unique));
+        assert_eq!(count_solutions(none, usize::MAX), 0);
+        assert_eq!(count_solutions(many, usize::MAX), 125);
+        assert_eq!(count_solutions(many, 10), 10);
+        assert!(!has_unique_solution(many));
+        assert_eq!(count_solutions(&format!("11{}", ".".repeat(79)), 2), 0);
+    }
+
+    #[test]
+    fn test_wrong_length() {
+        for input in [".".repeat(80), ".".repeat(82), String::new()] {
+            assert_eq!(count_solutions(&input, 2), 0);
+            assert!(!has_unique_solution(&input));
+            assert_eq!(solve(&input, Size::STANDARD), None);
+            assert_eq!(solution_path(&input), None);
+        }
+        assert_eq!(solve(&".".repeat(17), Size::new(2)), None);
+        assert_eq!(solve_in(&".".repeat(15), &Layout::latin_square(Size::new(2))), None);
+    }
+
+    #[test]
+    fn test_solve_sized() {
+        let size = Size::new(2);
+        assert_eq!(solve("1.3..4.2.1..4..1", size), Some("1234341221434321".to_string()));
+        assert!(has_unique_solution_sized("1.3..4.2.1..4..1", size));
+        assert_eq!(count_solutions_sized(&".".repeat(16), size, usize::MAX), 288);
+
+        // a pattern grid with every other cell of each row cleared
+        let size = Size::new(4);
+        let puzzle: String = (0..256)
+            .map(|i| {
+                let (r, c) = (i / 16, i % 16);
+                let v = ((r % 4) * 4 + r / 4 + c) % 16 + 1;
+                if (r + c) % 2 == 0 { char::from_digit(v as u32, 17).unwrap() } else { '.' }
+            })
+            .collect();
+        let solution = solve(&puzzle, size).unwrap();
+        assert!(!has_conflicts_sized(&str_to_vecu8_sized(&solution, size), size));
+        assert!(
+            puzzle
+                .chars()
+                .zip(solution.chars())
+                .all(|(p, s)| p == '.' || p.eq_ignore_ascii_case(&s))
+        );
+        assert_eq!(solve(&format!("11{}", ".".repeat(254)), size), None);
+
+        let size = Size::rectangular(3, 2);
+        let puzzle = "1.3....56......5.4.6...1.1.6.....3.2";
+        assert_eq!(solve(puzzle, size), Some("123456456123231564564231312645645312".to_string()));
+        assert!(has_unique_solution_sized(puzzle, size));
+    }
+
+    #[test]
+    fn test_solve_in() {
+        // a 4x4 board whose blocks are replaced by the two diagonals
+        let mut layout = Layout::latin_square(Size::new(2));
+        layout.add_house(House::Group(0), vec![0, 5, 10, 15]);
+        layout.add_house(House::Group(1), vec![3, 6, 9, 12]);
+
+        assert_eq!(count_solutions_in(&".".repeat(16), &layout, usize::MAX), 48);
+        let solution = solve_in("1.........3.....", &layout).unwrap();
+        assert!(!layout.has_conflicts(&str_to_vecu8(&solution)));
+        assert_eq!(solve_in("1....1..........", &layout), None);
+    }
+}
@@ -0,0 +1,517 @@
+use std::{ collections::HashMap, rc::Rc };
+
+use crate::{ constraint::digits, House, Layout, SolveStep, Technique };
+
+type Strategy = fn(&[u8], &[u32], &Layout) -> Option<SolveStep>;
+type Line = fn(usize) -> House;
+
+/// The elimination techniques, easiest first.
+const STRATEGIES: [Strategy; 11] = [
+    find_pointing,
+    find_claiming,
+    |p, c, l| find_naked_subset(p, c, l, 2),
+    |p, c, l| find_fish(p, c, l, 2),
+    |p, c, l| find_hidden_subset(p, c, l, 2),
+    |p, c, l| find_naked_subset(p, c, l, 3),
+    |p, c, l| find_fish(p, c, l, 3),
+    |p, c, l| find_hidden_subset(p, c, l, 3),
+    |p, c, l| find_naked_subset(p, c, l, 4),
+    |p, c, l| find_fish(p, c, l, 4),
+    |p, c, l| find_hidden_subset(p, c, l, 4),
+];
+
+/// Candidates of every cell as a bit set, bit `v` standing for value `v`. Filled cells and
+/// cells not calculated yet have none.
+pub fn candidate_masks(
+    puzzle: &[u8],
+    possible_values_map: &HashMap<usize, Rc<Vec<u8>>>
+) -> Vec<u32> {
+    (0..puzzle.len())
+        .map(|i| {
+            match possible_values_map.get(&i) {
+                Some(vs) if puzzle[i] == 0 => vs.iter().fold(0, |mask, &v| mask | (1 << v)),
+                _ => 0,
+            }
+        })
+        .collect()
+}
+
+fn mask_values(mask: u32) -> Vec<u8> {
+    digits(mask).collect()
+}
+
+fn combinations(items: &[usize], size: usize) -> Vec<Vec<usize>> {
+    if size == 0 {
+        return vec![vec![]];
+    }
+    if items.len() < size {
+        return vec![];
+    }
+    let mut res: Vec<Vec<usize>> = combinations(&items[1..], size - 1)
+        .into_iter()
+        .map(|mut c| {
+            c.insert(0, items[0]);
+            c
+        })
+        .collect();
+    res.append(&mut combinations(&items[1..], size));
+    res
+}
+
+/// Candidates of `value` in `cells` which are not in `keep`.
+fn eliminations_of(
+    candidates: &[u32],
+    cells: &[usize],
+    keep: &[usize],
+    values: u32
+) -> Vec<(usize, u8)> {
+    cells
+        .iter()
+        .filter(|i| !keep.contains(i))
+        .flat_map(|&i| {
+            mask_values(candidates[i] & values)
+                .into_iter()
+                .map(move |v| (i, v))
+        })
+        .collect()
+}
+
+pub(crate) fn is_line(house: House) -> bool {
+    matches!(house, House::Row(_) | House::Col(_))
+}
+
+/// A value confined to the cells a complete house `base` shares with another house `cover`
+/// is removed from the rest of `cover`. Bases and covers are picked among the houses of the
+/// layout by `is_base` and `is_cover`.
+fn locked_candidates(
+    candidates: &[u32],
+    layout: &Layout,
+    technique: Technique,
+    is_base: impl Fn(House) -> bool,
+    is_cover: impl Fn(House) -> bool
+) -> Option<SolveStep> {
+    let houses = layout.houses();
+    let bases = (0..houses.len()).filter(|&h| layout.is_complete(h) && is_base(houses[h].0));
+    for b in bases {
+        let (base, base_indexes) = &houses[b];
+        for value in 1..=layout.size().side() {
+            let cells: Vec<usize> = base_indexes
+                .iter()
+                .copied()
+                .filter(|&i| candidates[i] & (1 << value) != 0)
+                .collect();
+            if cells.len() < 2 {
+                continue;
+            }
+            for &c in layout.houses_of(cells[0]) {
+                let (cover, cover_indexes) = &houses[c];
+                if
+                    c == b ||
+                    !is_cover(*cover) ||
+                    !cells.iter().all(|i| cover_indexes.contains(i))
+                {
+                    continue;
+                }
+                let eliminations = eliminations_of(candidates, cover_indexes, &cells, 1 << value);
+                if !eliminations.is_empty() {
+                    return Some(SolveStep {
+                        technique,
+                        cells,
+                        digits: vec![value as u8],
+                        houses: vec![*base, *cover],
+                        placements: vec![],
+                        eliminations,
+                    });
+                }
+            }
+        }
+    }
+
+    None
+}
+
+/// A value with one place left in a complete house, looking at blocks and the other houses
+/// which are not lines before rows and columns.
+pub fn find_hidden_single(
+    puzzle: &[u8],
+    candidates: &[u32],
+    layout: &Layout
+) -> Option<SolveStep> {
+    let houses = layout.houses();
+    let (lines, others): (Vec<usize>, Vec<usize>) = (0..houses.len())
+        .filter(|&h| layout.is_complete(h))
+        .partition(|&h| is_line(houses[h].0));
+    for (house, indexes) in others.into_iter().chain(lines).map(|h| &houses[h]) {
+        for value in 1..=layout.size().side() as u8 {
+            if indexes.iter().any(|&i| puzzle[i] == value) {
+                continue;
+            }
+            let cells: Vec<usize> = indexes
+                .iter()
+                .copied()
+                .filter(|&i| candidates[i] & (1 << value) != 0)
+                .collect();
+            if cells.len() == 1 {
+                return Some(SolveStep::hidden_single(cells[0], value, *house));
+            }
+        }
+    }
+
+    None
+}
+
+/// A cell with one candidate left, left by the houses of the layout holding it.
+pub fn find_naked_single(
+    _puzzle: &[u8],
+    candidates: &[u32],
+    layout: &Layout
+) -> Option<SolveStep> {
+    let i = (0..candidates.len()).find(|&i| candidates[i].count_ones() == 1)?;
+    Some(SolveStep::naked_single(i, candidates[i].trailing_zeros() as u8, layout))
+}
+
+/// A value confined to one row or column of a block is removed from the rest of that line.
+/// Any other house of the layout, such as a window or a diagonal, may take the place of the
+/// block, and the value is then removed from any house holding all its places.
+pub fn find_pointing(_puzzle: &[u8], candidates: &[u32], layout: &Layout) -> Option<SolveStep> {
+    locked_candidates(candidates, layout, Technique::Pointing, |h| !is_line(h), |_| true)
+}
+
+/// A value confined to one block of a row or column is removed from the rest of that block,
+/// or of any other house of the layout which is not a line.
+pub fn find_claiming(_puzzle: &[u8], candidates: &[u32], layout: &Layout) -> Option<SolveStep> {
+    locked_candidates(candidates, layout, Technique::Claiming, is_line, |h| !is_line(h))
+}
+
+fn subset_technique(naked: bool, size: usize) -> Technique {
+    match (naked, size) {
+        (true, 2) => Technique::NakedPair,
+        (true, 3) => Technique::NakedTriple,
+        (true, _) => Technique::NakedQuad,
+        (false, 2) => Technique::HiddenPair,
+        (false, 3) => Technique::HiddenTriple,
+        (false, _) => Technique::HiddenQuad,
+    }
+}
+
+/// `size` cells of a house holding only `size` values between them.
+pub fn find_naked_subset(
+    _puzzle: &[u8],
+    candidates: &[u32],
+    layout: &Layout,
+    size: usize
+) -> Option<SolveStep> {
+    for (house, indexes) in layout.houses() {
+        let open: Vec<usize> = indexes
+            .iter()
+            .copied()
+            .filter(|&i| {
+                let count = candidates[i].count_ones() as usize;
+                count >= 2 && count <= size
+            })
+            .collect();
+        for cells in combinations(&open, size) {
+            let values = cells.iter().fold(0, |mask, &i| mask | candidates[i]);
+            if (values.count_ones() as usize) != size {
+                continue;
+            }
+            let eliminations = eliminations_of(candidates, indexes, &cells, values);
+            if !eliminations.is_empty() {
+                return Some(SolveStep {
+                    technique: subset_technique(true, size),
+                    cells,
+                    digits: mask_values(values),
+                    houses: vec![*house],
+                    placements: vec![],
+                    eliminations,
+                });
+            }
+        }
+    }
+
+    None
+}
+
+/// `size` values of a complete house which fit in only `size` cells between them.
+pub fn find_hidden_subset(
+    puzzle: &[u8],
+    candidates: &[u32],
+    layout: &Layout,
+    size: usize
+) -> Option<SolveStep> {
+    let houses = layout.houses();
+    let complete = (0..houses.len()).filter(|&h| layout.is_complete(h));
+    for (house, indexes) in complete.map(|h| &houses[h]) {
+        let positions = |v: usize| -> Vec<usize> {
+            indexes
+                .iter()
+                .copied()
+                .filter(|&i| candidates[i] & (1 << v) != 0)
+                .collect()
+        };
+        let open: Vec<usize> = (1..=layout.size().side())
+            .filter(|&v| indexes.iter().all(|&i| puzzle[i] as usize != v))
+            .filter(|&v| {
+                let count = positions(v).len();
+                count >= 2 && count <= size
+            })
+            .collect();
+        for values in combinations(&open, size) {
+            let mut cells: Vec<usize> = values.iter().flat_map(|&v| positions(v)).collect();
+            cells.sort();
+            cells.dedup();
+            if cells.len() != size {
+                continue;
+            }
+            let values_mask = values.iter().fold(0u32, |mask, &v| mask | (1 << v));
+            let eliminations: Vec<(usize, u8)> = cells
+                .iter()
+                .flat_map(|&i| {
+                    mask_values(candidates[i] & !values_mask)
+                        .into_iter()
+                        .map(move |v| (i, v))
+                })
+                .collect();
+            if !eliminations.is_empty() {
+                return Some(SolveStep {
+                    technique: subset_technique(false, size),
+                    cells,
+                    digits: values
+                        .iter()
+                        .map(|&v| v as u8)
+                        .collect(),
+                    houses: vec![*house],
+                    placements: vec![],
+                    eliminations,
+                });
+            }
+        }
+    }
+
+    None
+}
+
+/// A value confined to the same `size` columns in `size` rows (or the other way round) is
+/// removed from the rest of those columns. Only the rows and columns of the layout count.
+pub fn find_fish(
+    _puzzle: &[u8],
+    candidates: &[u32],
+    layout: &Layout,
+    size: usize
+) -> Option<SolveStep> {
+    let technique = match size {
+        2 => Technique::XWing,
+        3 => Technique::Swordfish,
+        _ => Technique::Jellyfish,
+    };
+    let lines: [(Line, Line); 2] = [
+        (House::Row, House::Col),
+        (House::Col, House::Row),
+    ];
+    let side = layout.size().side();
+    let cells_of = |line: House| layout.indexes(line).unwrap_or_default();
+
+    for value in 1..=side as u8 {
+        for (base_house, cover_house) in lines {
+            // the cover lines crossing each base line at a candidate of the value
+            let crossings: Vec<u32> = (0..side)
+                .map(|b| {
+                    cells_of(base_house(b))
+                        .iter()
+                        .enumerate()
+                        .filter(|&(_, &i)| candidates[i] & (1 << value) != 0)
+                        .fold(0, |mask, (c, _)| mask | (1 << c))
+                })
+                .collect();
+            let open: Vec<usize> = (0..side)
+                .filter(|&b| {
+                    let count = crossings[b].count_ones() as usize;
+                    count >= 2 && count <= size
+                })
+                .collect();
+            for bases in combinations(&open, size) {
+                let covers = bases.iter().fold(0u32, |mask, &b| mask | crossings[b]);
+                if (covers.count_ones() as usize) != size {
+                    continue;
+                }
+                let covers: Vec<usize> = (0..side).filter(|&c| covers & (1 << c) != 0).collect();
+                let cells: Vec<usize> = bases
+                    .iter()
+                    .flat_map(|&b| cells_of(base_house(b)))
+                    .copied()
+                    .filter(|&i| candidates[i] & (1 << value) != 0)
+                    .collect();
+                let cover_indexes: Vec<usize> = covers
+                    .iter()
+                    .flat_map(|&c| cells_of(cover_house(c)))
+                    .copied()
+                    .collect();
+                let eliminations = eliminations_of(candidates, &cover_indexes, &cells, 1 << value);
+                if !eliminations.is_empty() {
+                    return Some(SolveStep {
+                        technique,
+                        cells,
+                        digits: vec![value],
+                        houses: bases
+                            .iter()
+                            .map(|&b| base_house(b))
+                            .chain(covers.iter().map(|&c| cover_house(c)))
+                            .collect(),
+                        placements: vec![],
+                        eliminations,
+                    });
+                }
+            }
+        }
+    }
+
+    None
+}
+
+/// Applies the easiest elimination technique which makes progress on the puzzle.
+pub fn find_elimination(
+    puzzle: &[u8],
+    possible_values_map: &HashMap<usize, Rc<Vec<u8>>>
+) -> Option<SolveStep> {
+    find_elimination_in(puzzle, possible_values_map, &Layout::standard())
+}
+
+/// Same as `find_elimination` for a puzzle with the given layout, whose extra houses take part
+/// in the locked candidates and the subsets.
+pub fn find_elimination_in(
+    puzzle: &[u8],
+    possible_values_map: &HashMap<usize, Rc<Vec<u8>>>,
+    layout: &Layout
+) -> Option<SolveStep> {
+    let candidates = candidate_masks(puzzle, possible_values_map);
+    STRATEGIES.iter().find_map(|strategy| strategy(puzzle, &candidates, layout))
+}
+
+pub fn apply_eliminations(
+    possible_values_map: &mut HashMap<usize, Rc<Vec<u8>>>,
+    eliminations: &[(usize, u8)]
+) {
+    for &(i, v) in eliminations {
+        if let Some(vs) = possible_values_map.get_mut(&i) {
+            *vs = Rc::new(
+                vs
+                    .iter()
+                    .copied()
+                    .filter(|&value| value != v)
+                    .collect()
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ constraint::all_digits, str_to_vecu8, Size };
+
+    use super::*;
+
+    fn masks(puzzle: &[u8], pencil_marks: &[(usize, &[u8])]) -> Vec<u32> {
+        let side = Size::of_cells(puzzle.len()).unwrap().side();
+        let mut candidates: Vec<u32> = puzzle
+            .iter()
+            .map(|&v| if v == 0 { all_digits(side) } else { 0 })
+            .collect();
+        for &(i, vs) in pencil_marks {
+            candidates[i] = vs.iter().fold(0, |mask, &v| mask | (1 << v));
+        }
+        candidates
+    }
+
+    #[test]
+    fn test_combinations() {
+        assert_eq!(combinations(&[1, 2, 3], 2), vec![vec![1, 2], vec![1, 3], vec![2, 3]]);
+        assert_eq!(combinations(&[1, 2], 3), Vec::<Vec<usize>>::new());
+    }
+
+    #[test]
+    fn test_find_pointing() {
+        let puzzle = vec![0; 81];
+        // 5 only fits the top row of block 1
+        let mut candidates = masks(&puzzle, &[]);
+        for i in [9, 10, 11, 18, 19, 20] {
+            candidates[i] &= !(1 << 5);
+        }
+
+        let step = find_pointing(&puzzle, &candidates, &Layout::standard()).unwrap();
+        assert_eq!(step.technique, Technique::Pointing);
+        assert_eq!(step.houses, vec![House::Block(0), House::Row(0)]);
+        assert_eq!(step.eliminations, (3..9).map(|i| (i, 5)).collect::<Vec<(usize, u8)>>());
+    }
+
+    #[test]
+    fn test_find_naked_subset() {
+        let puzzle = str_to_vecu8(&".".repeat(81));
+        let candidates = masks(&puzzle, &[(0, &[1, 2]), (1, &[1, 2])]);
+
+        let step = find_naked_subset(&puzzle, &candidates, &Layout::standard(), 2).unwrap();
+        assert_eq!(step.technique, Technique::NakedPair);
+        assert_eq!(step.cells, vec![0, 1]);
+        assert_eq!(step.digits, vec![1, 2]);
+        assert_eq!(step.eliminations.len(), 14);
+    }
+
+    #[test]
+    fn test_find_fish() {
+        let puzzle = vec![0; 81];
+        // 7 only fits columns 1 and 5 in rows 1 and 4
+        let mut candidates = masks(&puzzle, &[]);
+        for i in (0..9).chain(27..36) {
+            if i % 9 != 0 && i % 9 != 4 {
+                candidates[i] &= !(1 << 7);
+            }
+        }
+
+        let step = find_fish(&puzzle, &candidates, &Layout::standard(), 2).unwrap();
+        assert_eq!(step.technique, Technique::XWing);
+        assert_eq!(step.cells, vec![0, 4, 27, 31]);
+        assert_eq!(step.eliminations.len(), 14);
+    }
+
+    #[test]
+    fn test_find_hidden_single_sized() {
+        let layout = Layout::new(Size::new(2));
+        let puzzle = vec![0; 16];
+        // 1 only fits the top left cell of block 1
+        let candidates = masks(&puzzle, &[(1, &[2, 3, 4]), (4, &[2, 3, 4]), (5, &[2, 3, 4])]);
+
+        let step = find_hidden_single(&puzzle, &candidates, &layout).unwrap();
+        assert_eq!(step.placements, vec![(0, 1)]);
+        assert_eq!(step.houses, vec![House::Block(0)]);
+    }
+
+    #[test]
+    fn test_find_elimination_sized() {
+        // on a 16x16, 15 and 16 are all the first two cells of row 1 can hold
+        let layout = Layout::new(Size::new(4));
+        let puzzle = vec![0; 256];
+        let all: Rc<Vec<u8>> = Rc::new((1..=16).collect());
+        let mut possible_values_map: HashMap<usize, Rc<Vec<u8>>> = (0..256)
+            .map(|i| (i, Rc::clone(&all)))
+            .collect();
+        possible_values_map.insert(0, Rc::new(vec![15, 16]));
+        possible_values_map.insert(1, Rc::new(vec![15, 16]));
+
+        let step = find_elimination_in(&puzzle, &possible_values_map, &layout).unwrap();
+        assert_eq!(step.technique, Technique::NakedPair);
+        assert_eq!(step.digits, vec![15, 16]);
+        assert_eq!(step.houses, vec![House::Row(0)]);
+        assert_eq!(step.eliminations.len(), 28);
+
+        // on a 4x4, 4 only fits columns 1 and 3 in rows 1 and 3
+        let layout = Layout::new(Size::new(2));
+        let puzzle = vec![0; 16];
+        let mut candidates = masks(&puzzle, &[]);
+        for i in [1, 3, 9, 11] {
+            candidates[i] &= !(1 << 4);
+        }
+        let step = find_fish(&puzzle, &candidates, &layout, 2).unwrap();
+        assert_eq!(step.technique, Technique::XWing);
+        assert_eq!(step.cells, vec![0, 2, 8, 10]);
+        assert_eq!(step.eliminations, vec![(4, 4), (12, 4), (6, 4), (14, 4)]);
+    }
+}
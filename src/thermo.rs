@@ -0,0 +1,129 @@
+use crate::{ are_touching, utils::{ clue_lines, parse_cells }, Size };
+
+/// A thermometer: its digits strictly increase from the bulb, the first cell, to the tip.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Thermometer {
+    pub cells: Vec<usize>,
+}
+
+impl Thermometer {
+    /// Raises the candidates of each cell above the lowest one of the cell before it, then
+    /// lowers them below the highest one of the cell after it.
+    pub fn prune(&self, masks: &mut [u32]) {
+        let mut low = 0;
+        for k in 0..masks.len() {
+            masks[k] &= !((2u32 << low) - 1);
+            if masks[k] == 0 {
+                masks.fill(0);
+                return;
+            }
+            low = masks[k].trailing_zeros();
+        }
+        let mut high = 31;
+        for k in (0..masks.len()).rev() {
+            masks[k] &= (1u32 << high) - 1;
+            if masks[k] == 0 {
+                masks.fill(0);
+                return;
+            }
+            high = 31 - masks[k].leading_zeros();
+        }
+    }
+}
+
+/// Reads thermometers, one per line of `clue_lines` as its cells from the bulb to the tip, e.g.
+/// `r1c1 r2c2 r2c3`. Returns `None` unless every thermometer has from 2 to as many cells as
+/// there are digits, none of them twice, each touching the one before by a side or a corner.
+pub fn parse_thermometers(text: &str, size: Size) -> Option<Vec<Thermometer>> {
+    let mut thermometers = vec![];
+    for line in clue_lines(text) {
+        let cells = parse_cells(line, size)?;
+        if
+            !(2..=size.side()).contains(&cells.len()) ||
+            (0..cells.len()).any(|k| cells[k + 1..].contains(&cells[k])) ||
+            !cells.windows(2).all(|pair| are_touching(pair[0], pair[1], size))
+        {
+            return None;
+        }
+        thermometers.push(Thermometer { cells });
+    }
+    Some(thermometers)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{ cell::RefCell, collections::HashMap, rc::Rc };
+
+    use crate::{ has_unique_solution_in, solve_in, GridTask, Layout, Propagation };
+
+    use super::*;
+
+    const THERMOMETERS: &str =
+        "r9c5 r9c4 r8c3 r7c3
+r7c8 r8c8 r9c8
+r2c6 r1c6 r1c7
+r4c2 r4c3 r4c4 r3c4
+r3c1 r4c1 r5c2
+r5c6 r4c6 r3c5
+r6c5 r7c4 r8c5
+r7c9 r6c8 r6c7 r6c6";
+    const PUZZLE: &str =
+        "65..8..1......2.............2.......7.....5...3....8...6.....5.......38.....5...4";
+    const SOLUTION: &str =
+        "652483917978162435314975628825736149791824563436519872269348751547291386183657294";
+
+    #[test]
+    fn test_parse_thermometers() {
+        let thermometers = parse_thermometers(THERMOMETERS, Size::STANDARD).unwrap();
+        assert_eq!(thermometers.len(), 8);
+        assert_eq!(thermometers[0], Thermometer { cells: vec![76, 75, 65, 56] });
+
+        // too short, a cell twice and a gap
+        assert_eq!(parse_thermometers("r1c1", Size::STANDARD), None);
+        assert_eq!(parse_thermometers("r1c1 r1c2 r1c1", Size::STANDARD), None);
+        assert_eq!(parse_thermometers("r1c1 r1c3", Size::STANDARD), None);
+    }
+
+    #[test]
+    fn test_prune_thermometer() {
+        let all = 0b11_1111_1110;
+        let thermometer = Thermometer { cells: vec![0, 1, 2] };
+
+        let mut masks = vec![all; 3];
+        thermometer.prune(&mut masks);
+        assert_eq!(masks, vec![0b00_1111_1110, 0b01_1111_1100, 0b11_1111_1000]);
+
+        // a 5 in the middle leaves 1 to 4 below it and 6 to 9 above it
+        let mut masks = vec![all, 1 << 5, all];
+        thermometer.prune(&mut masks);
+        assert_eq!(masks, vec![0b1_1110, 1 << 5, 0b11_1100_0000]);
+
+        let mut masks = vec![1 << 8, all, 1 << 9];
+        thermometer.prune(&mut masks);
+        assert!(masks.contains(&0));
+    }
+
+    #[test]
+    fn test_solve_thermo() {
+        let thermometers = parse_thermometers(THERMOMETERS, Size::STANDARD).unwrap();
+        let layout = Layout::thermo(Size::STANDARD, &thermometers);
+        assert_eq!(solve_in(PUZZLE, &layout), Some(SOLUTION.to_string()));
+        assert!(has_unique_solution_in(PUZZLE, &layout));
+        assert!(!has_unique_solution_in(PUZZLE, &Layout::standard()));
+        assert_eq!(layout.parse(&format!("{}1{}", ".".repeat(75), ".".repeat(5))), None);
+
+        let puzzle = Rc::new(RefCell::new(layout.parse(PUZZLE).unwrap()));
+        let propagation = GridTask::propagate(
+            Rc::clone(&puzzle),
+            Rc::new(RefCell::new(HashMap::new())),
+            Rc::new(layout)
+        );
+        assert!(!matches!(propagation, Propagation::Contradiction));
+        let filled = puzzle.borrow();
+        assert!(
+            SOLUTION.bytes()
+                .zip(filled.iter())
+                .all(|(s, &v)| v == 0 || v == s - b'0')
+        );
+    }
+}
@@ -0,0 +1,255 @@
+use crate::{ str_to_vecu8, vecu8_to_str };
+
+/// A validity preserving rearrangement of a grid: a permutation of the cells together with a
+/// relabeling of the digits. Transforms compose with `then` and undo with `inverse`, so a
+/// solution of a transformed puzzle maps back onto the original one.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Transform {
+    /// `cells[i]` is the cell whose value moves to cell `i`.
+    cells: Vec<usize>,
+    /// `digits[v]` is the digit which replaces `v`, with `digits[0]` keeping empty cells empty.
+    digits: Vec<u8>,
+}
+
+fn is_permutation(perm: &[usize]) -> bool {
+    (0..perm.len()).all(|i| perm.contains(&i))
+}
+
+impl Transform {
+    fn from_cells(cell: impl Fn(usize, usize) -> (usize, usize)) -> Self {
+        Transform {
+            cells: (0..81)
+                .map(|i| {
+                    let (r, c) = cell(i / 9, i % 9);
+                    r * 9 + c
+                })
+                .collect(),
+            digits: (0..=9).collect(),
+        }
+    }
+
+    pub fn identity() -> Self {
+        Transform::from_cells(|r, c| (r, c))
+    }
+
+    /// A quarter turn clockwise.
+    pub fn rotate() -> Self {
+        Transform::from_cells(|r, c| (8 - c, r))
+    }
+
+    pub fn transpose() -> Self {
+        Transform::from_cells(|r, c| (c, r))
+    }
+
+    /// Mirrors top to bottom.
+    pub fn mirror_horizontal() -> Self {
+        Transform::from_cells(|r, c| (8 - r, c))
+    }
+
+    /// Mirrors left to right.
+    pub fn mirror_vertical() -> Self {
+        Transform::from_cells(|r, c| (r, 8 - c))
+    }
+
+    /// Row `k` of the band becomes old row `perm[k]` of the band.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `band` is beyond 2 or `perm` is not a permutation of 0, 1 and 2.
+    pub fn permute_rows(band: usize, perm: [usize; 3]) -> Self {
+        assert!(band < 3 && is_permutation(&perm), "invalid row permutation");
+        Transform::from_cells(|r, c| {
+            if r / 3 == band { (band * 3 + perm[r % 3], c) } else { (r, c) }
+        })
+    }
+
+    /// Column `k` of the stack becomes old column `perm[k]` of the stack.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `stack` is beyond 2 or `perm` is not a permutation of 0, 1 and 2.
+    pub fn permute_cols(stack: usize, perm: [usize; 3]) -> Self {
+        assert!(stack < 3 && is_permutation(&perm), "invalid column permutation");
+        Transform::from_cells(|r, c| {
+            if c / 3 == stack { (r, stack * 3 + perm[c % 3]) } else { (r, c) }
+        })
+    }
+
+    /// Band `k` becomes old band `perm[k]`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `perm` is not a permutation of 0, 1 and 2.
+    pub fn permute_bands(perm: [usize; 3]) -> Self {
+        assert!(is_permutation(&perm), "invalid band permutation");
+        Transform::from_cells(|r, c| (perm[r / 3] * 3 + (r % 3), c))
+    }
+
+    /// Stack `k` becomes old stack `perm[k]`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `perm` is not a permutation of 0, 1 and 2.
+    pub fn permute_stacks(perm: [usize; 3]) -> Self {
+        assert!(is_permutation(&perm), "invalid stack permutation");
+        Transform::from_cells(|r, c| (r, perm[c / 3] * 3 + (c % 3)))
+    }
+
+    /// Digit `v` becomes `digits[v - 1]`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `digits` is not a permutation of 1 to 9.
+    pub fn relabel(digits: [u8; 9]) -> Self {
+        let perm: Vec<usize> = digits
+            .iter()
+            .map(|&v| (v as usize).wrapping_sub(1))
+            .collect();
+        assert!(is_permutation(&perm), "invalid digit relabeling");
+        Transform {
+            cells: (0..81).collect(),
+            digits: [0].into_iter().chain(digits).collect(),
+        }
+    }
+
+    /// This transform followed by `next`.
+    pub fn then(&self, next: &Transform) -> Self {
+        Transform {
+            cells: next.cells
+                .iter()
+                .map(|&i| self.cells[i])
+                .collect(),
+            digits: self.digits
+                .iter()
+                .map(|&v| next.digits[v as usize])
+                .collect(),
+        }
+    }
+
+    pub fn inverse(&self) -> Self {
+        let mut cells = vec![0; self.cells.len()];
+        self.cells
+            .iter()
+            .enumerate()
+            .for_each(|(i, &from)| {
+                cells[from] = i;
+            });
+        let mut digits = vec![0; self.digits.len()];
+        self.digits
+            .iter()
+            .enumerate()
+            .for_each(|(v, &to)| {
+                digits[to as usize] = v as u8;
+            });
+
+        Transform { cells, digits }
+    }
+
+    /// The transformed grid, or `None` unless the grid has 81 cells holding digits up to 9.
+    pub fn apply_vec(&self, grid: &[u8]) -> Option<Vec<u8>> {
+        if grid.len() != self.cells.len() {
+            return None;
+        }
+        self.cells
+            .iter()
+            .map(|&from| self.digits.get(grid[from] as usize).copied())
+            .collect()
+    }
+
+    pub fn apply(&self, puzzle: &str) -> Option<String> {
+        self.apply_vec(&str_to_vecu8(puzzle)).map(|grid| vecu8_to_str(&grid))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ count_solutions, has_conflicts };
+
+    use super::*;
+
+    const PUZZLE: &str =
+        ".5..83.17...1..4..3.4..56.8....3...9.9.8245....6....7...9....5...729..861.36.72.4";
+    const SOLUTION: &str =
+        "652483917978162435314975628825736149791824563436519872269348751547291386183657294";
+
+    #[test]
+    fn test_rotate() {
+        let rotated = Transform::rotate().apply(SOLUTION).unwrap();
+        // the first column, read bottom up
+        assert_eq!(&rotated[..9], "152478396");
+
+        let full_turn = (0..4).fold(Transform::identity(), |t, _| t.then(&Transform::rotate()));
+        assert_eq!(full_turn, Transform::identity());
+        assert_eq!(
+            Transform::rotate().then(&Transform::rotate()),
+            Transform::mirror_horizontal().then(&Transform::mirror_vertical())
+        );
+    }
+
+    #[test]
+    fn test_permutations() {
+        let rows: Vec<&str> = (0..9).map(|r| &SOLUTION[r * 9..r * 9 + 9]).collect();
+
+        // a cycle, which unlike a swap is not its own inverse
+        let permuted = Transform::permute_rows(1, [1, 2, 0]).apply(SOLUTION).unwrap();
+        assert_eq!(&permuted[27..36], rows[4]);
+        assert_eq!(&permuted[36..45], rows[5]);
+        assert_eq!(&permuted[45..54], rows[3]);
+        let inverse = Transform::permute_rows(1, [2, 0, 1]);
+        assert_eq!(Transform::permute_rows(1, [1, 2, 0]).inverse(), inverse);
+
+        let permuted = Transform::permute_bands([1, 2, 0]).apply(SOLUTION).unwrap();
+        assert_eq!(&permuted[..27], rows[3..6].concat());
+
+        let column = |grid: &str, c: usize| -> String {
+            (0..9).map(|r| grid.as_bytes()[r * 9 + c] as char).collect()
+        };
+        let permuted = Transform::permute_cols(0, [1, 2, 0]).apply(SOLUTION).unwrap();
+        assert_eq!(column(&permuted, 0), column(SOLUTION, 1));
+        assert_eq!(column(&permuted, 2), column(SOLUTION, 0));
+        let permuted = Transform::permute_stacks([1, 2, 0]).apply(SOLUTION).unwrap();
+        assert_eq!(column(&permuted, 0), column(SOLUTION, 3));
+    }
+
+    #[test]
+    fn test_transforms_preserve_validity() {
+        let transforms = [
+            Transform::rotate(),
+            Transform::transpose(),
+            Transform::mirror_horizontal(),
+            Transform::mirror_vertical(),
+            Transform::permute_rows(1, [2, 0, 1]),
+            Transform::permute_cols(2, [1, 0, 2]),
+            Transform::permute_bands([1, 2, 0]),
+            Transform::permute_stacks([2, 1, 0]),
+            Transform::relabel([9, 8, 7, 6, 5, 4, 3, 2, 1]),
+        ];
+        for transform in transforms {
+            let solution = transform.apply(SOLUTION).unwrap();
+            assert!(!has_conflicts(&str_to_vecu8(&solution)));
+            assert_eq!(count_solutions(&transform.apply(PUZZLE).unwrap(), 2), 1);
+        }
+    }
+
+    #[test]
+    fn test_inverse() {
+        let transform = Transform::rotate()
+            .then(&Transform::permute_rows(0, [1, 2, 0]))
+            .then(&Transform::permute_stacks([2, 0, 1]))
+            .then(&Transform::relabel([2, 3, 4, 5, 6, 7, 8, 9, 1]));
+        let puzzle = transform.apply(PUZZLE).unwrap();
+        let solution = transform.apply(SOLUTION).unwrap();
+
+        assert_eq!(transform.then(&transform.inverse()), Transform::identity());
+        assert_eq!(transform.inverse().apply(&puzzle).unwrap(), PUZZLE);
+        assert_eq!(transform.inverse().apply(&solution).unwrap(), SOLUTION);
+    }
+
+    #[test]
+    fn test_apply_wrong_length() {
+        assert_eq!(Transform::rotate().apply("123"), None);
+        assert_eq!(Transform::rotate().apply(&format!("{}1", SOLUTION)), None);
+        assert_eq!(Transform::identity().apply_vec(&[10; 81]), None);
+        assert_eq!(Transform::identity().apply_vec(&[]), None);
+    }
+}
@@ -1,17 +1,30 @@
+use crate::{ Alphabet, Size };
+
 pub fn str_to_vecu8(sudoku: &str) -> Vec<u8> {
+    str_to_vecu8_sized(sudoku, Size::STANDARD)
+}
+
+/// Parses a puzzle of the given size, where digits above 9 are written as letters
+/// (`A` for 10, `B` for 11 and so on) and any other symbol is an empty cell.
+pub fn str_to_vecu8_sized(sudoku: &str, size: Size) -> Vec<u8> {
     String::from(sudoku)
         .chars()
-        .map(|c| { (if let Some(n) = c.to_digit(10) { n } else { 0 }) as u8 })
+        .map(|c| c.to_digit(size.side() as u32 + 1).unwrap_or_default() as u8)
         .collect()
 }
 
-pub fn vecu8_to_str(vec: &Vec<u8>) -> String {
+pub fn vecu8_to_str(vec: &[u8]) -> String {
+    vecu8_to_str_sized(vec, Size::STANDARD)
+}
+
+pub fn vecu8_to_str_sized(vec: &[u8], size: Size) -> String {
+    let radix = size.side() as u32 + 1;
     // implemented via String::from_iter()
     String::from_iter(
         vec.iter().map(|&n| {
             match n {
                 0 => '.',
-                _ => char::from_digit(n as u32, 10).unwrap(),
+                _ => char::from_digit(n as u32, radix).unwrap().to_ascii_uppercase(),
             }
         })
     )
@@ -27,51 +40,191 @@ pub fn vecu8_to_str(vec: &Vec<u8>) -> String {
 }
 
 pub fn show(sudoku: &str) {
-    println!("+------+------+------+");
+    show_sized(sudoku, Size::STANDARD);
+}
+
+pub fn show_sized(sudoku: &str, size: Size) {
+    print!("{}", render_marked(sudoku, size, |_| ' '));
+}
+
+/// The boxed grid of `show_sized` for a puzzle in the usual notation, written with the given
+/// alphabet instead, or `None` if the alphabet has no symbol for one of its digits.
+pub fn render_with(sudoku: &str, size: Size, alphabet: &Alphabet) -> Option<String> {
+    let sudoku = alphabet.format(&str_to_vecu8_sized(sudoku, size))?;
+    Some(render_marked(&sudoku, size, |_| ' '))
+}
+
+/// Prints the grid of `render_with`, or nothing if the alphabet is too short for the board.
+pub fn show_with(sudoku: &str, size: Size, alphabet: &Alphabet) {
+    if let Some(rendered) = render_with(sudoku, size, alphabet) {
+        print!("{}", rendered);
+    }
+}
+
+/// The boxed grid printed by `show`, with `mark(i)` written in front of cell `i` instead of a
+/// space, so that variants can point out their special cells.
+pub fn render_marked(sudoku: &str, size: Size, mark: impl Fn(usize) -> char) -> String {
+    let side = size.side();
+    let segment = format!("{}+", "-".repeat(size.box_width() * 2));
+    let border = format!("+{}", segment.repeat(side / size.box_width()));
+    let mut output = format!("{}\n", border);
     for (i, c) in sudoku.chars().enumerate() {
-        if i % 9 == 0 {
+        if i % side == 0 {
             if i != 0 {
-                println!("");
-                if i % 27 == 0 {
-                    println!("+------+------+------+");
+                output.push('\n');
+                if i % (side * size.box_height()) == 0 {
+                    output.push_str(&format!("{}\n", border));
                 }
             }
-            print!("|");
+            output.push('|');
         }
 
-        print!(" {}", c);
-        if (i + 1) % 3 == 0 {
-            print!("|");
+        output.push(mark(i));
+        output.push(c);
+        if (i + 1) % size.box_width() == 0 {
+            output.push('|');
         }
     }
 
-    println!("\n+------+------+------+");
+    output.push_str(&format!("\n{}\n", border));
+    output
+}
+
+pub fn cell_name(i: usize) -> String {
+    cell_name_sized(i, Size::STANDARD)
+}
+
+pub fn cell_name_sized(i: usize, size: Size) -> String {
+    format!("r{}c{}", size.row_of(i) + 1, size.col_of(i) + 1)
+}
+
+/// Reads a cell name such as `r1c2`, the reverse of `cell_name` for a board of any size.
+pub fn parse_cell(name: &str, size: Size) -> Option<usize> {
+    let (row, col) = name.strip_prefix(['r', 'R'])?.split_once(['c', 'C'])?;
+    let (row, col) = (row.parse::<usize>().ok()?, col.parse::<usize>().ok()?);
+    if !(1..=size.side()).contains(&row) || !(1..=size.side()).contains(&col) {
+        return None;
+    }
+    Some((row - 1) * size.side() + col - 1)
+}
+
+/// The lines of a text describing the clues of a variant, one clue per line, trimmed. Blank
+/// lines and lines starting with `#` are skipped, and the cells or numbers listed on a line may
+/// be separated by spaces, tabs or commas, e.g. `15: r1c1 r1c2,r2c1`.
+pub fn clue_lines(text: &str) -> impl Iterator<Item = &str> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+}
+
+/// Splits a list of a clue line into its items, see `clue_lines`.
+pub(crate) fn split_items(list: &str) -> impl Iterator<Item = &str> {
+    list.split([' ', ',', '\t']).filter(|item| !item.is_empty())
+}
+
+/// Reads a list of cell names, e.g. `r1c1 r1c2,r2c1`.
+pub(crate) fn parse_cells(list: &str, size: Size) -> Option<Vec<usize>> {
+    split_items(list)
+        .map(|name| parse_cell(name, size))
+        .collect()
+}
+
+/// Whether two different cells touch by a side or a corner.
+pub fn are_touching(i: usize, j: usize, size: Size) -> bool {
+    let rows = size.row_of(i).abs_diff(size.row_of(j));
+    let cols = size.col_of(i).abs_diff(size.col_of(j));
+    i != j && rows <= 1 && cols <= 1
+}
+
+/// The number of the block holding a cell, counting blocks row by row.
+pub fn block_of(i: usize) -> usize {
+    Size::STANDARD.block_of(i)
 }
 
 pub fn conjugate_row_index(i: usize) -> Vec<usize> {
-    let row_head = (i / 9) * 9;
-    (row_head..row_head + 9).collect()
+    Size::STANDARD.conjugate_row_index(i)
 }
 
 pub fn conjugate_col_index(i: usize) -> Vec<usize> {
-    let col_head = i % 9;
-    (col_head..81).step_by(9).collect()
+    Size::STANDARD.conjugate_col_index(i)
 }
 
 pub fn conjugate_block_index(i: usize) -> Vec<usize> {
-    let y = i % 9;
-    let y = (y / 3) * 3;
-    let x = i / 9;
-    let x = (x / 3) * 3;
-    let res: Vec<usize> = (x * 9 + y..x * 9 + y + 27).step_by(9).collect();
-    let res = res
-        .iter()
-        .flat_map(|&n| {
-            let vec: Vec<usize> = (n..n + 3).collect();
-            vec
+    Size::STANDARD.conjugate_block_index(i)
+}
+
+/// Whether the cells form one piece, going from cell to cell across their sides.
+pub fn is_connected(cells: &[usize], size: Size) -> bool {
+    let mut reached = vec![cells[0]];
+    let mut k = 0;
+    while k < reached.len() {
+        let i = reached[k];
+        let next: Vec<usize> = cells
+            .iter()
+            .copied()
+            .filter(|&j| {
+                let rows = size.row_of(i).abs_diff(size.row_of(j));
+                let cols = size.col_of(i).abs_diff(size.col_of(j));
+                rows + cols == 1 && !reached.contains(&j)
+            })
+            .collect();
+        reached.extend(next);
+        k += 1;
+    }
+    reached.len() == cells.len()
+}
+
+/// Every pair of cells side by side, the left or upper cell first.
+pub fn adjacent_pairs(size: Size) -> Vec<[usize; 2]> {
+    let side = size.side();
+    (0..size.cells())
+        .flat_map(|i| {
+            let right = (size.col_of(i) + 1 < side).then_some([i, i + 1]);
+            let below = (size.row_of(i) + 1 < side).then_some([i, i + side]);
+            right.into_iter().chain(below)
+        })
+        .collect()
+}
+
+/// Reads marks between cells side by side, one per line of `clue_lines` as a label, a colon and
+/// the two cells, e.g. `w: r1c1 r1c2`. Returns `None` unless the cells of every mark are side
+/// by side and hold no other mark.
+pub(crate) fn parse_pair_marks(text: &str, size: Size) -> Option<Vec<(String, [usize; 2])>> {
+    let pairs = adjacent_pairs(size);
+    let mut marks: Vec<(String, [usize; 2])> = vec![];
+    for line in clue_lines(text) {
+        let (label, cells) = line.split_once(':')?;
+        let cells = parse_cells(cells, size)?;
+        if cells.len() != 2 {
+            return None;
+        }
+        let cells = [cells[0].min(cells[1]), cells[0].max(cells[1])];
+        if !pairs.contains(&cells) || marks.iter().any(|&(_, other)| other == cells) {
+            return None;
+        }
+        marks.push((label.trim().to_string(), cells));
+    }
+    Some(marks)
+}
+
+/// Whether some digit appears twice in a row, column or block.
+pub fn has_conflicts(puzzle: &[u8]) -> bool {
+    has_conflicts_sized(puzzle, Size::STANDARD)
+}
+
+pub fn has_conflicts_sized(puzzle: &[u8], size: Size) -> bool {
+    (0..puzzle.len())
+        .filter(|&i| puzzle[i] != 0)
+        .any(|i| {
+            [
+                size.conjugate_row_index(i),
+                size.conjugate_col_index(i),
+                size.conjugate_block_index(i),
+            ]
+                .iter()
+                .flatten()
+                .any(|&j| j != i && puzzle[j] == puzzle[i])
         })
-        .collect();
-    res
 }
 
 #[cfg(test)]
@@ -167,7 +320,7 @@ mod tests {
             4
         ];
 
-        assert_eq!(str_to_vecu8(&input), expected_output)
+        assert_eq!(str_to_vecu8(input), expected_output)
     }
 
     #[test]
@@ -264,7 +417,7 @@ mod tests {
 
     #[test]
     fn test_same_row_index() {
-        let inputs = vec![0, 79];
+        let inputs = [0, 79];
         let expected_outputs = vec![
             vec![0, 1, 2, 3, 4, 5, 6, 7, 8],
             vec![72, 73, 74, 75, 76, 77, 78, 79, 80]
@@ -277,7 +430,7 @@ mod tests {
 
     #[test]
     fn test_same_col_index() {
-        let inputs = vec![0, 77];
+        let inputs = [0, 77];
         let expected_outputs = vec![
             vec![0, 9, 18, 27, 36, 45, 54, 63, 72],
             vec![5, 14, 23, 32, 41, 50, 59, 68, 77]
@@ -290,7 +443,7 @@ mod tests {
 
     #[test]
     fn test_same_block_index() {
-        let inputs = vec![41, 77, 70];
+        let inputs = [41, 77, 70];
         let expected_outputs = vec![
             vec![30, 31, 32, 39, 40, 41, 48, 49, 50],
             vec![57, 58, 59, 66, 67, 68, 75, 76, 77],
@@ -302,10 +455,81 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_has_conflicts() {
+        let input =
+            ".5..83.17...1..4..3.4..56.8....3...9.9.8245....6....7...9....5...729..861.36.72.4";
+        assert!(!has_conflicts(&str_to_vecu8(input)));
+        assert!(has_conflicts(&str_to_vecu8(&input.replacen('.', "5", 1))));
+    }
+
+    #[test]
+    fn test_sized() {
+        let size = Size::new(4);
+        let input = "1234..........G.".repeat(16);
+        let puzzle = str_to_vecu8_sized(&input, size);
+        assert_eq!(&puzzle[..4], &[1, 2, 3, 4]);
+        assert_eq!(puzzle[14], 16);
+        assert_eq!(vecu8_to_str_sized(&puzzle, size), input);
+        assert!(has_conflicts_sized(&puzzle, size));
+
+        let size = Size::new(2);
+        assert!(!has_conflicts_sized(&str_to_vecu8_sized("12..34..........", size), size));
+        assert!(has_conflicts_sized(&str_to_vecu8_sized("12..21..........", size), size));
+        show_sized("1234341221434321", size);
+        let expected_output =
+            "+----+----+
+|>1 2| 3 4|
+| 3 4| 1 2|
++----+----+
+| 2 1| 4 3|
+| 4 3| 2 1|
++----+----+
+";
+        assert_eq!(
+            render_marked("1234341221434321", size, |i| if i == 0 { '>' } else { ' ' }),
+            expected_output
+        );
+
+        let size = Size::rectangular(3, 2);
+        let input = "123456456123231564564231312645645312";
+        let puzzle = str_to_vecu8_sized(input, size);
+        assert!(!has_conflicts_sized(&puzzle, size));
+        assert!(has_conflicts_sized(&puzzle, Size::rectangular(2, 3)));
+        show_sized(input, size);
+    }
+
+    #[test]
+    fn test_parse_cell() {
+        assert_eq!(parse_cell("r1c2", Size::STANDARD), Some(1));
+        assert_eq!(parse_cell(&cell_name(80), Size::STANDARD), Some(80));
+        assert_eq!(parse_cell("R16C16", Size::new(4)), Some(255));
+        assert_eq!(cell_name_sized(255, Size::new(4)), "r16c16");
+        assert_eq!(parse_cell("r0c1", Size::STANDARD), None);
+        assert_eq!(parse_cell("c1r1", Size::STANDARD), None);
+        assert_eq!(adjacent_pairs(Size::new(2))[..3], [[0, 1], [0, 4], [1, 2]]);
+        assert_eq!(adjacent_pairs(Size::STANDARD).len(), 144);
+    }
+
+    #[test]
+    fn test_clue_lines() {
+        let text = "  # cages\n15: r1c1 r1c2,r2c1\n\n\t8: r9c9  \n";
+        let lines: Vec<&str> = clue_lines(text).collect();
+        assert_eq!(lines, vec!["15: r1c1 r1c2,r2c1", "8: r9c9"]);
+        let items: Vec<&str> = split_items(" r1c1 r1c2,r2c1\tr3c3 ,").collect();
+        assert_eq!(items, vec!["r1c1", "r1c2", "r2c1", "r3c3"]);
+    }
+
     #[test]
     fn test_show() {
         let input =
             ".....5..3..9....4..81.4.......7.......4..2..68...14.3.......2...4...6..79...5..1.";
         show(input);
+
+        let letters = Alphabet::new("A-I", "_").unwrap();
+        let rendered = render_with(input, Size::STANDARD, &letters).unwrap();
+        assert_eq!(rendered.lines().nth(1), Some("| _ _ _| _ _ E| _ _ C|"));
+        show_with(input, Size::STANDARD, &letters);
+        assert_eq!(render_with(input, Size::STANDARD, &Alphabet::new("A-H", "_").unwrap()), None);
     }
 }
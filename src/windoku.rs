@@ -0,0 +1,124 @@
+use crate::{ render_marked, House, Size };
+
+/// The boxed grid of `show` with the cells of the drawn windows marked by `#`, the phantom
+/// windows being left for the reader to find.
+pub fn render_windoku(sudoku: &str, size: Size) -> String {
+    let b = size.box_width();
+    let drawn: Vec<usize> = (0..size.side())
+        .filter(|w| w / b + 1 < b && w % b + 1 < b)
+        .flat_map(|w| House::Window(w).indexes_sized(size).unwrap_or_default())
+        .collect();
+    render_marked(sudoku, size, |i| if drawn.contains(&i) { '#' } else { ' ' })
+}
+
+pub fn show_windoku(sudoku: &str, size: Size) {
+    print!("{}", render_windoku(sudoku, size));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{ collections::HashMap, rc::Rc };
+
+    use crate::{
+        find_elimination_in,
+        has_unique_solution_in,
+        rate,
+        rate_in,
+        solution_path_in,
+        solve_in,
+        str_to_vecu8,
+        vecu8_to_str,
+        Layout,
+        Technique,
+    };
+
+    use super::*;
+
+    const PUZZLE: &str =
+        ".3...1...............9.4.6...6.7...5........238.....1...........2....95.........4";
+    const SOLUTION: &str =
+        "639251748458367129172984563216473895947815632385692417594128376823746951761539284";
+
+    #[test]
+    fn test_windoku_layout() {
+        let layout = Layout::windoku(Size::STANDARD);
+        assert_eq!(layout.houses().len(), 36);
+        assert_eq!(layout.peers(10).len(), 23);
+        assert_eq!(layout.peers(0).len(), 24);
+        assert!((27..36).all(|h| layout.is_complete(h)));
+
+        // r2c2 and r4c4 share window 1, r1c1 and r5c5 share a phantom window
+        for (i, j) in [(10, 30), (0, 40)] {
+            let mut puzzle = vec!['.'; 81];
+            puzzle[i] = '1';
+            puzzle[j] = '1';
+            let puzzle: String = puzzle.into_iter().collect();
+            assert_eq!(layout.parse(&puzzle), None);
+            assert!(Layout::standard().parse(&puzzle).is_some());
+        }
+    }
+
+    #[test]
+    fn test_solve_windoku() {
+        let layout = Layout::windoku(Size::STANDARD);
+        assert_eq!(solve_in(PUZZLE, &layout), Some(SOLUTION.to_string()));
+        assert!(has_unique_solution_in(PUZZLE, &layout));
+        assert!(!has_unique_solution_in(PUZZLE, &Layout::standard()));
+    }
+
+    #[test]
+    fn test_windoku_solution_path() {
+        let layout = Layout::windoku(Size::STANDARD);
+        let steps = solution_path_in(PUZZLE, &layout).unwrap();
+        let mut puzzle = str_to_vecu8(PUZZLE);
+        for &(i, v) in steps.iter().flat_map(|step| step.placements.iter()) {
+            assert_eq!(puzzle[i], 0);
+            puzzle[i] = v;
+        }
+        assert_eq!(vecu8_to_str(&puzzle), SOLUTION);
+        assert!(
+            steps
+                .iter()
+                .any(|step| {
+                    step.technique == Technique::HiddenSingle &&
+                        matches!(step.houses[0], House::Window(_))
+                })
+        );
+
+        // the windows spare the guesses the standard rules would need
+        assert!(!rate_in(PUZZLE, &layout).unwrap().guessed);
+        assert!(rate(PUZZLE).unwrap().guessed);
+    }
+
+    #[test]
+    fn test_window_subset() {
+        // r2c2 and r4c4 only share window 1, which they fill with 1 and 2
+        let puzzle = vec![0; 81];
+        let all: Vec<u8> = (1..=9).collect();
+        let mut possible_values_map: HashMap<usize, Rc<Vec<u8>>> = (0..81)
+            .map(|i| (i, Rc::new(all.clone())))
+            .collect();
+        for i in [10, 30] {
+            possible_values_map.insert(i, Rc::new(vec![1, 2]));
+        }
+
+        assert_eq!(find_elimination_in(&puzzle, &possible_values_map, &Layout::standard()), None);
+        let step = find_elimination_in(
+            &puzzle,
+            &possible_values_map,
+            &Layout::windoku(Size::STANDARD)
+        ).unwrap();
+        assert_eq!(step.technique, Technique::NakedPair);
+        assert_eq!(step.houses, vec![House::Window(0)]);
+        assert_eq!(step.eliminations.len(), 14);
+    }
+
+    #[test]
+    fn test_render_windoku() {
+        let rendered = render_windoku(SOLUTION, Size::STANDARD);
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines[1], "| 6 3 9| 2 5 1| 7 4 8|");
+        assert_eq!(lines[2], "| 4#5#8|#3 6#7|#1#2 9|");
+        show_windoku(PUZZLE, Size::STANDARD);
+    }
+}
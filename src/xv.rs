@@ -0,0 +1,231 @@
+use crate::{ adjacent_pairs, constraint::prune_pair, utils::parse_pair_marks, Size };
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum XvKind {
+    /// The digits add up to 10.
+    X,
+    /// The digits add up to 5.
+    V,
+    /// No marker where every marker is given: the digits add up to neither 5 nor 10.
+    Blank,
+}
+
+/// An XV marker, or its absence, between two cells side by side.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Xv {
+    pub cells: [usize; 2],
+    pub kind: XvKind,
+}
+
+impl Xv {
+    fn fits(&self, a: u8, b: u8) -> bool {
+        match self.kind {
+            XvKind::X => a + b == 10,
+            XvKind::V => a + b == 5,
+            XvKind::Blank => a + b != 5 && a + b != 10,
+        }
+    }
+
+    /// Keeps the candidates of each cell which fit some candidate of the other.
+    pub fn prune(&self, masks: &mut [u32]) {
+        prune_pair(masks, |a, b| self.fits(a, b));
+    }
+}
+
+/// Reads XV markers, one per line as `x` or `v`, a colon and the two cells it sits between,
+/// e.g. `x: r1c1 r2c1`, in the format of `parse_dots`.
+pub fn parse_xv(text: &str, size: Size) -> Option<Vec<Xv>> {
+    parse_pair_marks(text, size)?
+        .into_iter()
+        .map(|(label, cells)| {
+            let kind = match label.as_str() {
+                "x" | "X" => XvKind::X,
+                "v" | "V" => XvKind::V,
+                _ => {
+                    return None;
+                }
+            };
+            Some(Xv { cells, kind })
+        })
+        .collect()
+}
+
+/// The blank markers between every two cells side by side which hold none of `markers`, for
+/// puzzles where all the markers are given.
+pub fn negative_xv(markers: &[Xv], size: Size) -> Vec<Xv> {
+    adjacent_pairs(size)
+        .into_iter()
+        .filter(|&cells| markers.iter().all(|marker| marker.cells != cells))
+        .map(|cells| Xv { cells, kind: XvKind::Blank })
+        .collect()
+}
+
+/// Draws a grid with the markers between their cells, in the style of `render_jigsaw` with
+/// the boxes as regions, e.g. for the top half of a 4×4:
+///
+/// ```text
+/// +---+---+---+---+
+/// | 1 V 4 | 3   2 |
+/// +   +   +   + X +
+/// | 3   2 | 1   4 |
+/// ```
+pub fn render_xv(sudoku: &str, size: Size, markers: &[Xv]) -> String {
+    let side = size.side();
+    let cells: Vec<char> = sudoku.chars().collect();
+    let marker = |a: usize, b: usize| {
+        markers
+            .iter()
+            .find(|m| m.cells == [a, b])
+            .and_then(|m| {
+                match m.kind {
+                    XvKind::X => Some('X'),
+                    XvKind::V => Some('V'),
+                    XvKind::Blank => None,
+                }
+            })
+    };
+    let border = |r: usize| {
+        let mut line = String::new();
+        for c in 0..side {
+            let i = r * side + c;
+            let wall = r.is_multiple_of(size.box_height());
+            let line_char = if wall { '-' } else { ' ' };
+            let middle = match r {
+                _ if r == 0 || r == side => line_char,
+                _ => marker(i - side, i).unwrap_or(line_char),
+            };
+            line.push_str(&format!("+{}{}{}", line_char, middle, line_char));
+        }
+        line.push_str("+\n");
+        line
+    };
+
+    let mut output = String::new();
+    for r in 0..side {
+        output.push_str(&border(r));
+        for c in 0..side {
+            let i = r * side + c;
+            let wall = if c.is_multiple_of(size.box_width()) { '|' } else { ' ' };
+            let separator = match c {
+                0 => wall,
+                _ => marker(i - 1, i).unwrap_or(wall),
+            };
+            output.push_str(&format!("{} {} ", separator, cells[i]));
+        }
+        output.push_str("|\n");
+    }
+    output.push_str(&border(side));
+    output
+}
+
+pub fn show_xv(sudoku: &str, size: Size, markers: &[Xv]) {
+    print!("{}", render_xv(sudoku, size, markers));
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ has_unique_solution_in, solve_in, Layout };
+
+    use super::*;
+
+    // every marker of the solution
+    const MARKERS: &str =
+        "x: r1c3 r2c3
+v: r1c4 r2c4
+v: r1c6 r2c6
+x: r1c7 r1c8
+x: r2c4 r3c4
+x: r2c7 r3c7
+v: r2c8 r3c8
+v: r3c2 r3c3
+x: r3c5 r4c5
+x: r3c8 r3c9
+x: r4c1 r4c2
+x: r4c4 r4c5
+v: r4c5 r5c5
+x: r4c6 r5c6
+v: r4c7 r4c8
+x: r4c8 r5c8
+x: r5c2 r5c3
+x: r5c4 r5c5
+v: r5c9 r6c9
+x: r6c5 r6c6
+v: r6c5 r7c5
+x: r7c2 r8c2
+v: r7c4 r8c4
+x: r7c7 r8c7
+x: r8c3 r9c3
+x: r8c5 r8c6
+v: r8c7 r9c7
+x: r8c9 r9c9";
+    const SOLUTION: &str =
+        "652483917978162435314975628825736149791824563436519872269348751547291386183657294";
+
+    #[test]
+    fn test_parse_xv() {
+        let markers = parse_xv(MARKERS, Size::STANDARD).unwrap();
+        assert_eq!(markers.len(), 28);
+        assert_eq!(markers[0], Xv { cells: [2, 11], kind: XvKind::X });
+        assert_eq!(negative_xv(&markers, Size::STANDARD).len(), 144 - 28);
+
+        let markers = parse_xv("V: r1c2 r1c1", Size::STANDARD).unwrap();
+        assert_eq!(markers, vec![Xv { cells: [0, 1], kind: XvKind::V }]);
+        assert_eq!(parse_xv("x: r1c1 r1c3", Size::STANDARD), None);
+        assert_eq!(parse_xv("w: r1c1 r1c2", Size::STANDARD), None);
+    }
+
+    #[test]
+    fn test_prune_xv() {
+        let all = 0b11_1111_1110;
+        let marker = |kind| Xv { cells: [0, 1], kind };
+
+        let mut masks = vec![1 << 3, all];
+        marker(XvKind::X).prune(&mut masks);
+        assert_eq!(masks, vec![1 << 3, 1 << 7]);
+
+        let mut masks = vec![all, all];
+        marker(XvKind::V).prune(&mut masks);
+        assert_eq!(masks, vec![0b1_1110; 2]);
+
+        let mut masks = vec![1 << 5, all];
+        marker(XvKind::Blank).prune(&mut masks);
+        assert_eq!(masks, vec![1 << 5, all & !(1 << 5)]);
+
+        let mut masks = vec![1 << 5, 1 << 1];
+        marker(XvKind::X).prune(&mut masks);
+        assert_eq!(masks, vec![0, 0]);
+    }
+
+    #[test]
+    fn test_solve_xv() {
+        let markers = parse_xv(MARKERS, Size::STANDARD).unwrap();
+
+        let puzzle = format!(".....3{}6{}", ".".repeat(18), ".".repeat(56));
+        let layout = Layout::xv(Size::STANDARD, &markers, true);
+        assert_eq!(solve_in(&puzzle, &layout), Some(SOLUTION.to_string()));
+        assert!(has_unique_solution_in(&puzzle, &layout));
+        assert!(!has_unique_solution_in(&puzzle, &Layout::xv(Size::STANDARD, &markers, false)));
+
+        let puzzle = format!("........7{}6{}", ".".repeat(15), ".".repeat(56));
+        let layout = Layout::xv(Size::STANDARD, &markers, false);
+        assert_eq!(solve_in(&puzzle, &layout), Some(SOLUTION.to_string()));
+        assert!(has_unique_solution_in(&puzzle, &layout));
+        // 1 and 4 side by side add up to 5 without a V
+        let puzzle = format!("14{}", ".".repeat(79));
+        assert!(layout.parse(&puzzle).is_some());
+        assert_eq!(Layout::xv(Size::STANDARD, &markers, true).parse(&puzzle), None);
+    }
+
+    #[test]
+    fn test_render_xv() {
+        let markers = parse_xv(MARKERS, Size::STANDARD).unwrap();
+        let rendered = render_xv(SOLUTION, Size::STANDARD, &markers);
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines.len(), 19);
+        assert_eq!(lines[0], format!("{}+", "+---".repeat(9)));
+        assert_eq!(lines[1], "| 6   5   2 | 4   8   3 | 9 X 1   7 |");
+        assert_eq!(lines[2], "+   +   + X + V +   + V +   +   +   +");
+        assert_eq!(lines[6], "+---+---+---+---+-X-+---+---+---+---+");
+        show_xv(SOLUTION, Size::STANDARD, &markers);
+    }
+}